@@ -0,0 +1,51 @@
+//! Measures how far each sampler's 16 spp render of the crystal preset is
+//! from a high sample count reference, as quoted in the README:
+//!
+//!     cargo run --release --example sampler_rmse
+//!
+//! The error is the RMSE over every channel of the linear radiance, clamped
+//! to [0, 1] so the few very bright pixels do not dominate.
+
+use rustytracer::framebuffer::Framebuffer;
+use rustytracer::rtweekend::seed_rng;
+use rustytracer::sampler::SamplerKind;
+use rustytracer::scenes;
+
+const LAYOUT_SEED: u64 = 1;
+const SAMPLE_SEED: u64 = 7;
+const WIDTH: i32 = 200;
+const SAMPLES: i32 = 16;
+const REFERENCE_SAMPLES: i32 = 2048;
+
+fn main() {
+    let scene = scenes::find("crystal").expect("crystal preset");
+    seed_rng(LAYOUT_SEED);
+    let world = scene.build_world();
+
+    let render = |sampler: SamplerKind, samples_per_pixel: i32, seed: u64| {
+        let mut camera = scene.default_camera();
+        camera.image_width = WIDTH;
+        camera.samples_per_pixel = samples_per_pixel;
+        camera.sampler = sampler;
+        camera.seed = Some(seed);
+        camera.render(world.as_ref())
+    };
+
+    // The reference uses a different sample seed, so its own noise is
+    // independent of every image measured against it.
+    let reference = render(SamplerKind::Sobol, REFERENCE_SAMPLES, SAMPLE_SEED + 1);
+    for sampler in SamplerKind::ALL {
+        let image = render(sampler, SAMPLES, SAMPLE_SEED);
+        println!("{:<12} RMSE {:.3} at {} spp", sampler.name(), rmse(&image, &reference), SAMPLES);
+    }
+}
+
+fn rmse(image: &Framebuffer, reference: &Framebuffer) -> f64 {
+    let squared: f64 = image
+        .pixels
+        .iter()
+        .zip(&reference.pixels)
+        .flat_map(|(a, b)| (0..3).map(move |axis| (a[axis].clamp(0.0, 1.0) - b[axis].clamp(0.0, 1.0)).powi(2)))
+        .sum();
+    (squared / (3 * image.pixels.len()) as f64).sqrt()
+}
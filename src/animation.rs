@@ -0,0 +1,294 @@
+use std::fs;
+use std::io;
+use std::ops::{Add, Mul, Sub};
+use std::path::Path;
+
+use crate::camera::Camera;
+use crate::rtweekend::degrees_to_radians;
+use crate::vec3::{cross, dot, unit_vector, Point3};
+
+/// How camera parameters move between keyframes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+  /// Straight lines, with a kink at every keyframe.
+  Linear,
+  /// Smooth curves through every keyframe, with tangents from the
+  /// neighbouring keys (spaced by their frame numbers).
+  #[default]
+  CatmullRom,
+}
+
+impl Interpolation {
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name.to_ascii_lowercase().as_str() {
+      "linear" => Some(Interpolation::Linear),
+      "catmull-rom" | "catmullrom" | "smooth" => Some(Interpolation::CatmullRom),
+      _ => None,
+    }
+  }
+}
+
+/// Camera parameters set at one frame. Parameters a key leaves out keep the
+/// value of the key before, or the scene camera's for the first key.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CameraKey {
+  pub frame: f64,
+  pub lookfrom: Option<Point3>,
+  pub lookat: Option<Point3>,
+  pub vfov: Option<f64>,
+  pub focus_dist: Option<f64>,
+  pub defocus_angle: Option<f64>,
+}
+
+/// The animated camera parameters, fully resolved for one key.
+#[derive(Debug, Clone, Copy)]
+struct CameraState {
+  lookfrom: Point3,
+  lookat: Point3,
+  vfov: f64,
+  focus_dist: f64,
+  defocus_angle: f64,
+}
+
+impl CameraState {
+  fn capture(camera: &Camera) -> Self {
+    CameraState {
+      lookfrom: camera.lookfrom,
+      lookat: camera.lookat,
+      vfov: camera.vfov,
+      focus_dist: camera.focus_dist,
+      defocus_angle: camera.defocus_angle,
+    }
+  }
+
+  fn with_key(self, key: &CameraKey) -> Self {
+    CameraState {
+      lookfrom: key.lookfrom.unwrap_or(self.lookfrom),
+      lookat: key.lookat.unwrap_or(self.lookat),
+      vfov: key.vfov.unwrap_or(self.vfov),
+      focus_dist: key.focus_dist.unwrap_or(self.focus_dist),
+      defocus_angle: key.defocus_angle.unwrap_or(self.defocus_angle),
+    }
+  }
+
+  fn apply(&self, camera: &mut Camera) {
+    camera.lookfrom = self.lookfrom;
+    camera.lookat = self.lookat;
+    camera.vfov = self.vfov;
+    camera.focus_dist = self.focus_dist;
+    camera.defocus_angle = self.defocus_angle;
+  }
+}
+
+/// Keyframed camera moves. Frames before the first key and after the last
+/// hold the end keys.
+#[derive(Debug, Clone, Default)]
+pub struct CameraPath {
+  /// Keys in frame order.
+  keys: Vec<CameraKey>,
+  pub interpolation: Interpolation,
+}
+
+impl CameraPath {
+  /// Fails if two keys share a frame number, since the path would have to
+  /// jump between them.
+  pub fn new(mut keys: Vec<CameraKey>, interpolation: Interpolation) -> io::Result<Self> {
+    keys.sort_by(|a, b| a.frame.total_cmp(&b.frame));
+    if let Some(pair) = keys.windows(2).find(|pair| pair[0].frame == pair[1].frame) {
+      return Err(invalid(&format!("two camera keyframes at frame {}", pair[0].frame)));
+    }
+    Ok(CameraPath { keys, interpolation })
+  }
+
+  /// Reads keys from text with one key per line: the frame number, then
+  /// any of `lookfrom=x,y,z`, `lookat=x,y,z`, `vfov=deg`, `focus-dist=d`
+  /// and `defocus-angle=deg`. Blank lines and `#` comments are skipped.
+  pub fn parse(text: &str, interpolation: Interpolation) -> io::Result<Self> {
+    let mut keys = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+      let line = line.split('#').next().unwrap_or("").trim();
+      if line.is_empty() {
+        continue;
+      }
+      let error = |message: String| invalid(&format!("line {}: {}", index + 1, message));
+      let mut fields = line.split_whitespace();
+      let frame = fields.next().unwrap_or("");
+      let mut key = CameraKey {
+        frame: frame.parse().map_err(|_| error(format!("invalid frame number '{}'", frame)))?,
+        ..CameraKey::default()
+      };
+      if !key.frame.is_finite() {
+        return Err(error(format!("invalid frame number '{}'", frame)));
+      }
+      for field in fields {
+        let (name, value) = field
+          .split_once('=')
+          .ok_or_else(|| error(format!("expected name=value, found '{}'", field)))?;
+        let number = |value: &str| value.parse::<f64>().map_err(|_| error(format!("invalid value '{}' for {}", value, name)));
+        match name {
+          "lookfrom" | "lookat" => {
+            let components = value.split(',').map(number).collect::<io::Result<Vec<f64>>>()?;
+            let [x, y, z] = components[..] else {
+              return Err(error(format!("{} needs x,y,z", name)));
+            };
+            let point = Point3::from_values(x, y, z);
+            if name == "lookfrom" {
+              key.lookfrom = Some(point);
+            } else {
+              key.lookat = Some(point);
+            }
+          }
+          "vfov" => key.vfov = Some(number(value)?),
+          "focus-dist" => key.focus_dist = Some(number(value)?),
+          "defocus-angle" => key.defocus_angle = Some(number(value)?),
+          _ => return Err(error(format!("unknown camera parameter '{}'", name))),
+        }
+      }
+      keys.push(key);
+    }
+    if keys.is_empty() {
+      return Err(invalid("no camera keyframes"));
+    }
+    CameraPath::new(keys, interpolation)
+  }
+
+  /// Sets the animated parameters of `camera` for `frame`, starting from the
+  /// camera's own values.
+  pub fn apply(&self, frame: f64, camera: &mut Camera) {
+    if self.keys.is_empty() {
+      return;
+    }
+    let mut state = CameraState::capture(camera);
+    let states: Vec<CameraState> = self
+      .keys
+      .iter()
+      .map(|key| {
+        state = state.with_key(key);
+        state
+      })
+      .collect();
+    let times: Vec<f64> = self.keys.iter().map(|key| key.frame).collect();
+
+    let next = times.partition_point(|&time| time <= frame);
+    if next == 0 || next == times.len() {
+      states[next.max(1) - 1].apply(camera);
+      return;
+    }
+    let segment = next - 1;
+    let track = |value: fn(&CameraState) -> f64| {
+      let values: Vec<f64> = states.iter().map(value).collect();
+      self.interpolate(&times, &values, segment, frame)
+    };
+    let point_track = |value: fn(&CameraState) -> Point3| {
+      let values: Vec<Point3> = states.iter().map(value).collect();
+      self.interpolate(&times, &values, segment, frame)
+    };
+    CameraState {
+      lookfrom: point_track(|state| state.lookfrom),
+      lookat: point_track(|state| state.lookat),
+      vfov: track(|state| state.vfov),
+      focus_dist: track(|state| state.focus_dist),
+      defocus_angle: track(|state| state.defocus_angle),
+    }
+    .apply(camera);
+  }
+
+  /// Value at `frame` within the segment from key `i` to key `i + 1`.
+  fn interpolate<T>(&self, times: &[f64], values: &[T], i: usize, frame: f64) -> T
+  where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f64, Output = T>,
+  {
+    let span = times[i + 1] - times[i];
+    let s = (frame - times[i]) / span;
+    let (p1, p2) = (values[i], values[i + 1]);
+    match self.interpolation {
+      Interpolation::Linear => p1 + (p2 - p1) * s,
+      Interpolation::CatmullRom => {
+        // Per-frame slopes from the keys either side; the end keys use
+        // their only neighbour.
+        let slope = |k: usize| {
+          let (a, b) = (k.saturating_sub(1), (k + 1).min(values.len() - 1));
+          (values[b] - values[a]) * (1.0 / (times[b] - times[a]))
+        };
+        let (m1, m2) = (slope(i) * span, slope(i + 1) * span);
+        let (s2, s3) = (s * s, s * s * s);
+        p1 * (2.0 * s3 - 3.0 * s2 + 1.0) + m1 * (s3 - 2.0 * s2 + s) + p2 * (3.0 * s2 - 2.0 * s3) + m2 * (s3 - s2)
+      }
+    }
+  }
+}
+
+pub fn load_camera_path<P: AsRef<Path>>(path: P, interpolation: Interpolation) -> io::Result<CameraPath> {
+  CameraPath::parse(&fs::read_to_string(path)?, interpolation)
+}
+
+/// Turntable: circles the camera around `lookat`, about the `vup` axis,
+/// keeping its height and distance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Orbit {
+  /// Turn over the whole animation; 360 loops seamlessly.
+  pub degrees: f64,
+  pub first_frame: u32,
+  /// Frames the turn is spread over. The last frame stops one step short
+  /// of `degrees`, so a full turn does not repeat its first frame.
+  pub frames: u32,
+}
+
+impl Orbit {
+  pub fn apply(&self, frame: f64, camera: &mut Camera) {
+    let angle = degrees_to_radians(self.degrees * (frame - self.first_frame as f64) / self.frames.max(1) as f64);
+    let axis = unit_vector(&camera.vup);
+    let offset = camera.lookfrom - camera.lookat;
+    // Rodrigues' rotation of the offset about the axis.
+    let rotated = offset * angle.cos() + cross(&axis, &offset) * angle.sin() + axis * (dot(&axis, &offset) * (1.0 - angle.cos()));
+    camera.lookfrom = camera.lookat + rotated;
+  }
+}
+
+/// Everything that changes the camera from frame to frame: a keyframed
+/// path, then an orbit around wherever it looks.
+#[derive(Debug, Clone, Default)]
+pub struct CameraAnimation {
+  pub path: Option<CameraPath>,
+  pub orbit: Option<Orbit>,
+}
+
+impl CameraAnimation {
+  pub fn apply(&self, frame: f64, camera: &mut Camera) {
+    if let Some(path) = &self.path {
+      path.apply(frame, camera);
+    }
+    if let Some(orbit) = &self.orbit {
+      orbit.apply(frame, camera);
+    }
+  }
+}
+
+fn invalid(message: &str) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn duplicate_frames_are_rejected() {
+    let text = "0 vfov=20\n# hold\n60 vfov=30\n\n60.0 vfov=40\n";
+    let error = CameraPath::parse(text, Interpolation::Linear).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(error.to_string(), "two camera keyframes at frame 60");
+
+    let key = CameraKey { frame: 10.0, ..CameraKey::default() };
+    assert!(CameraPath::new(vec![key, key], Interpolation::Linear).is_err());
+  }
+
+  #[test]
+  fn non_finite_frames_are_rejected_with_their_line() {
+    for frame in ["NaN", "inf", "-infinity"] {
+      let text = format!("0 vfov=20\n{} vfov=30\n", frame);
+      let error = CameraPath::parse(&text, Interpolation::Linear).unwrap_err();
+      assert_eq!(error.to_string(), format!("line 2: invalid frame number '{}'", frame));
+    }
+  }
+}
@@ -0,0 +1,171 @@
+use crate::exr::{write_exr, ExrChannel};
+use crate::hittable::HitRecord;
+use crate::vec3::{dot, Point3, Vec3};
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::sync::Arc;
+
+/// Arbitrary output variable: a per-pixel pass recorded alongside the
+/// beauty image from the first surface seen through the pixel centre.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Aov {
+  /// Distance along the camera's viewing axis.
+  Depth,
+  /// World-space shading normal (`HitRecord::normal`).
+  Normal,
+  /// World-space hit position.
+  Position,
+  /// Reflectance of the first surface hit.
+  Albedo,
+  /// Dense material index, 1-based in order of first appearance.
+  MaterialId,
+  /// Dense primitive index, 1-based in order of first appearance.
+  ObjectId,
+  /// Number of samples the pixel actually received.
+  SampleCount,
+}
+
+impl Aov {
+  pub const ALL: [Aov; 7] = [
+    Aov::Depth,
+    Aov::Normal,
+    Aov::Position,
+    Aov::Albedo,
+    Aov::MaterialId,
+    Aov::ObjectId,
+    Aov::SampleCount,
+  ];
+
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name.to_ascii_lowercase().as_str() {
+      "depth" | "z" => Some(Aov::Depth),
+      "normal" | "n" => Some(Aov::Normal),
+      "position" | "p" => Some(Aov::Position),
+      "albedo" => Some(Aov::Albedo),
+      "material-id" | "material" => Some(Aov::MaterialId),
+      "object-id" | "object" => Some(Aov::ObjectId),
+      "sample-count" | "samples" | "spp" => Some(Aov::SampleCount),
+      _ => None,
+    }
+  }
+
+  /// Layer name used in EXR channel names and side-car file names.
+  pub fn name(&self) -> &'static str {
+    match self {
+      Aov::Depth => "depth",
+      Aov::Normal => "normal",
+      Aov::Position => "position",
+      Aov::Albedo => "albedo",
+      Aov::MaterialId => "materialId",
+      Aov::ObjectId => "objectId",
+      Aov::SampleCount => "sampleCount",
+    }
+  }
+
+  pub fn channel_names(&self) -> &'static [&'static str] {
+    match self {
+      Aov::Depth => &["Z"],
+      Aov::Normal | Aov::Position => &["X", "Y", "Z"],
+      Aov::Albedo => &["R", "G", "B"],
+      Aov::MaterialId | Aov::ObjectId => &["id"],
+      Aov::SampleCount => &["count"],
+    }
+  }
+}
+
+/// One AOV for the whole image, stored as one plane per channel.
+#[derive(Debug, Clone)]
+pub struct AovLayer {
+  pub aov: Aov,
+  pub planes: Vec<Vec<f32>>,
+}
+
+impl AovLayer {
+  /// Channels named `<layer>.<channel>`, e.g. `normal.X`.
+  pub fn exr_channels(&self) -> Vec<ExrChannel<'_>> {
+    self
+      .aov
+      .channel_names()
+      .iter()
+      .zip(&self.planes)
+      .map(|(channel, plane)| ExrChannel { name: format!("{}.{}", self.aov.name(), channel), data: plane })
+      .collect()
+  }
+}
+
+/// Writes a single AOV as its own EXR file.
+pub fn write_aov_exr<W: Write>(out: &mut W, width: usize, height: usize, layer: &AovLayer) -> io::Result<()> {
+  let mut out = io::BufWriter::new(out);
+  write_exr(&mut out, width, height, &mut layer.exr_channels())?;
+  out.flush()
+}
+
+/// What the pixel-centre ray saw. Identities are raw pointers at this point
+/// and are turned into dense IDs once the whole image is known.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AovSample {
+  pub hit: bool,
+  pub depth: f64,
+  pub normal: Vec3,
+  pub position: Point3,
+  pub albedo: Vec3,
+  pub material_key: usize,
+  pub object_key: usize,
+}
+
+impl AovSample {
+  pub fn from_hit(rec: &HitRecord, camera_center: Point3, view_dir: Vec3) -> Self {
+    let (albedo, material_key) = match &rec.mat {
+      Some(mat) => (mat.albedo(), Arc::as_ptr(mat) as *const () as usize),
+      None => (Vec3::new(), 0),
+    };
+    AovSample {
+      hit: true,
+      depth: dot(&(rec.p - camera_center), &view_dir),
+      normal: rec.normal,
+      position: rec.p,
+      albedo,
+      material_key,
+      object_key: rec.object_key,
+    }
+  }
+}
+
+/// Builds the requested layers from per-pixel samples and sample counts in
+/// scanline order. Pixels that saw nothing are zero in every geometric
+/// layer, and ID 0 means background.
+pub fn build_layers(aovs: &[Aov], samples: &[AovSample], sample_counts: &[u32]) -> Vec<AovLayer> {
+  aovs
+    .iter()
+    .map(|&aov| {
+      let planes = match aov {
+        Aov::Depth => vec![samples.iter().map(|s| s.depth as f32).collect()],
+        Aov::Normal => vector_planes(samples, |s| s.normal),
+        Aov::Position => vector_planes(samples, |s| s.position),
+        Aov::Albedo => vector_planes(samples, |s| s.albedo),
+        Aov::MaterialId => vec![dense_ids(samples, |s| s.material_key)],
+        Aov::ObjectId => vec![dense_ids(samples, |s| s.object_key)],
+        Aov::SampleCount => vec![sample_counts.iter().map(|&n| n as f32).collect()],
+      };
+      AovLayer { aov, planes }
+    })
+    .collect()
+}
+
+fn vector_planes(samples: &[AovSample], field: impl Fn(&AovSample) -> Vec3) -> Vec<Vec<f32>> {
+  (0..3).map(|axis| samples.iter().map(|s| field(s)[axis] as f32).collect()).collect()
+}
+
+fn dense_ids(samples: &[AovSample], key: impl Fn(&AovSample) -> usize) -> Vec<f32> {
+  let mut ids = HashMap::new();
+  samples
+    .iter()
+    .map(|s| {
+      if !s.hit {
+        return 0.0;
+      }
+      let next = ids.len() + 1;
+      *ids.entry(key(s)).or_insert(next) as f32
+    })
+    .collect()
+}
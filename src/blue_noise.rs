@@ -0,0 +1,124 @@
+use crate::rtweekend::hash_u64;
+use std::sync::OnceLock;
+
+/// Side length of the tileable blue-noise mask.
+pub const SIZE: usize = 64;
+
+const SIGMA: f64 = 1.9;
+
+/// Value of the blue-noise mask at (x, y), wrapping around the tile. Values
+/// are uniformly distributed in `(0, 1)`.
+pub fn sample(x: usize, y: usize) -> f64 {
+  mask()[(y % SIZE) * SIZE + (x % SIZE)] as f64
+}
+
+/// The mask is generated once with Ulichney's void-and-cluster method, which
+/// ranks every cell so that any threshold of the mask is evenly spread.
+pub fn mask() -> &'static [f32] {
+  static MASK: OnceLock<Vec<f32>> = OnceLock::new();
+  MASK.get_or_init(void_and_cluster)
+}
+
+struct Energy {
+  kernel: Vec<f64>,
+  values: Vec<f64>,
+}
+
+impl Energy {
+  fn new() -> Self {
+    let mut kernel = vec![0.0; SIZE * SIZE];
+    for dy in 0..SIZE {
+      for dx in 0..SIZE {
+        // Toroidal distance, so the finished mask tiles seamlessly.
+        let fx = dx.min(SIZE - dx) as f64;
+        let fy = dy.min(SIZE - dy) as f64;
+        kernel[dy * SIZE + dx] = (-(fx * fx + fy * fy) / (2.0 * SIGMA * SIGMA)).exp();
+      }
+    }
+    Energy { kernel, values: vec![0.0; SIZE * SIZE] }
+  }
+
+  fn update(&mut self, index: usize, sign: f64) {
+    let (px, py) = (index % SIZE, index / SIZE);
+    for y in 0..SIZE {
+      let dy = (y + SIZE - py) % SIZE;
+      for x in 0..SIZE {
+        let dx = (x + SIZE - px) % SIZE;
+        self.values[y * SIZE + x] += sign * self.kernel[dy * SIZE + dx];
+      }
+    }
+  }
+
+  /// Densest occupied cell, i.e. the tightest cluster.
+  fn tightest_cluster(&self, pattern: &[bool]) -> usize {
+    (0..pattern.len())
+      .filter(|&i| pattern[i])
+      .max_by(|&a, &b| self.values[a].total_cmp(&self.values[b]))
+      .unwrap()
+  }
+
+  /// Emptiest free cell, i.e. the largest void.
+  fn largest_void(&self, pattern: &[bool]) -> usize {
+    (0..pattern.len())
+      .filter(|&i| !pattern[i])
+      .min_by(|&a, &b| self.values[a].total_cmp(&self.values[b]))
+      .unwrap()
+  }
+}
+
+fn void_and_cluster() -> Vec<f32> {
+  let n = SIZE * SIZE;
+  let initial = n / 10;
+
+  // Seed pattern: a fixed pseudo-random set of cells.
+  let mut pattern = vec![false; n];
+  let mut energy = Energy::new();
+  let mut placed = 0;
+  let mut counter = 0u64;
+  while placed < initial {
+    let index = (hash_u64(counter) % n as u64) as usize;
+    counter += 1;
+    if !pattern[index] {
+      pattern[index] = true;
+      energy.update(index, 1.0);
+      placed += 1;
+    }
+  }
+
+  // Move points from clusters into voids until the pattern is stable.
+  for _ in 0..n {
+    let cluster = energy.tightest_cluster(&pattern);
+    pattern[cluster] = false;
+    energy.update(cluster, -1.0);
+
+    let void = energy.largest_void(&pattern);
+    pattern[void] = true;
+    energy.update(void, 1.0);
+
+    if void == cluster {
+      break;
+    }
+  }
+
+  let mut ranks = vec![0usize; n];
+
+  // Phase 1: rank the initial points by repeatedly removing the tightest cluster.
+  let mut working = pattern.clone();
+  let mut working_energy = Energy { kernel: energy.kernel.clone(), values: energy.values.clone() };
+  for rank in (0..initial).rev() {
+    let cluster = working_energy.tightest_cluster(&working);
+    working[cluster] = false;
+    working_energy.update(cluster, -1.0);
+    ranks[cluster] = rank;
+  }
+
+  // Phase 2: fill the remaining cells, always into the largest void.
+  for rank in initial..n {
+    let void = energy.largest_void(&pattern);
+    pattern[void] = true;
+    energy.update(void, 1.0);
+    ranks[void] = rank;
+  }
+
+  ranks.iter().map(|&rank| ((rank as f64 + 0.5) / n as f64) as f32).collect()
+}
@@ -6,6 +6,7 @@ use crate::vec3::Point3;
 use std::sync::Arc;
 use std::cmp::Ordering;
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Debug)]
 pub struct AABB { 
     pub min: Point3,
@@ -61,6 +62,12 @@ impl AABB {
     }
 }
 
+impl Default for AABB {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub struct BVHNode {
     left: Arc<dyn Hittable>,
     right: Arc<dyn Hittable>,
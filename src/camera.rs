@@ -1,12 +1,33 @@
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use rayon::iter::{IntoParallelIterator, ParallelBridge, ParallelIterator};
+use std::cell::Cell;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-use crate::vec3::{cross, random_in_unit_disk, unit_vector, Point3, Vec3};
+use crate::vec3::{cross, sample_unit_disk, unit_vector, Point3, Vec3};
 use crate::ray::Ray;
-use crate::color::{Color, write_color};
+use crate::aov::{build_layers, Aov, AovSample};
+use crate::cancel::CancelToken;
+use crate::color::Color;
+use crate::framebuffer::{Accumulator, Framebuffer, PixelSum};
 use crate::hittable::{Hittable, HitRecord};
+use crate::filter::{Filter, SplatTile};
 use crate::interval::Interval;
-use crate::rtweekend::{degrees_to_radians, random_float, INFINITY};
-use std::io::{self, Write};
+use crate::projection::Projection;
+use crate::progress::{Progress, ProgressBar, ProgressTracker};
+use crate::tiles::{self, Tile, TileOrder};
+use crate::rtweekend::{degrees_to_radians, sample_seed, seed_rng, INFINITY};
+use crate::sampler::{self, Sampler, SamplerKind};
+use crate::stereo::{Convergence, Stereo, StereoLayout};
+
+/// Pass size used by time-budgeted and adaptive renders that do not set
+/// `pass_samples`; small passes let the deadline cut in evenly across the
+/// image and let pixels stop soon after they converge.
+const DEFAULT_PASS_SAMPLES: i32 = 4;
+
+thread_local! {
+  /// Rays traced on this thread since the last tile was reported.
+  static RAYS_TRACED: Cell<u64> = const { Cell::new(0) };
+}
 
 pub struct Camera {
   pub aspect_ratio: f64,
@@ -14,14 +35,61 @@ pub struct Camera {
   pub samples_per_pixel: i32,
   pub max_depth: i32,
   pub vfov: f64,
+  pub projection: Projection,
   pub lookfrom: Point3,
   pub lookat: Point3,
   pub vup: Vec3,
   pub defocus_angle: f64,
   pub focus_dist: f64,
+  /// Camera rays sample times between when the shutter opens and closes,
+  /// blurring whatever moves in between. Equal times freeze the scene.
+  pub shutter_open: f64,
+  pub shutter_close: f64,
+  /// Camera rays that miss everything produce transparent black instead of
+  /// the sky, and alpha records coverage.
+  pub transparent_background: bool,
+  /// Samples per pixel in each progressive pass; 0 renders everything in
+  /// one pass.
+  pub pass_samples: i32,
+  /// Edge length of the square tiles the image is rendered in.
+  pub tile_size: usize,
+  pub tile_order: TileOrder,
+  /// Only render this part of the image. Rays are generated exactly as for
+  /// the full frame, so the region lines up with a full render.
+  pub crop: Option<CropWindow>,
+  /// Keep the full-size frame (black and transparent outside the crop)
+  /// instead of returning just the cropped region.
+  pub crop_full_frame: bool,
+  /// Keep adding passes until this much wall-clock time has passed, up to
+  /// `samples_per_pixel`. Tiles not started in time keep fewer samples.
+  pub time_budget: Option<Duration>,
+  /// Stop sampling pixels once they converge and spend the savings on
+  /// noisy ones.
+  pub adaptive: Option<AdaptiveSampling>,
+  /// Cancelling stops the render as soon as the pixels in flight are done;
+  /// `render` then returns what has been accumulated so far.
+  pub cancel: CancelToken,
+  /// Extra passes to record alongside the beauty image.
+  pub aovs: Vec<Aov>,
+  /// Where sample values come from, for the camera and every bounce.
+  pub sampler: SamplerKind,
+  /// Reconstruction filter the samples are weighted with.
+  pub filter: Filter,
+  /// Seed for the sample generator of a fresh render; `None` picks a random
+  /// one. The same seed renders the same image on any number of threads.
+  pub seed: Option<u64>,
+  /// Render a left and right eye pair into one image, each eye
+  /// `image_width` wide.
+  pub stereo: Option<Stereo>,
 
   image_height: i32,
-  pixel_samples_scale: f64,
+  /// The single view, or the left and right eyes of a stereo pair.
+  views: Vec<View>,
+}
+
+/// Where the rays of one view start and how they spread over the image.
+#[derive(Debug, Clone, Copy)]
+struct View {
   center: Point3,
   pixel00_loc: Point3,
   pixel_delta_u: Vec3,
@@ -31,6 +99,10 @@ pub struct Camera {
   w: Vec3,
   defocus_disk_u : Vec3,
   defocus_disk_v: Vec3,
+  focus_dist: f64,
+  /// Sideways offset of the eye from `center` for omni-directional stereo,
+  /// negative for the left eye.
+  eye_offset: f64,
 }
 
 impl Camera {
@@ -41,149 +113,890 @@ impl Camera {
       samples_per_pixel: 10,
       max_depth: 10,
       vfov: 90.0,
+      projection: Projection::Perspective,
       lookfrom: Point3::from_values(0.0, 0.0, 0.0),
       lookat: Point3::from_values(0.0, 0.0, -1.0),
       vup: Vec3::from_values(0.0, 1.0, 0.0),
       defocus_angle: 0.0,
       focus_dist: 10.0,
-
+      shutter_open: 0.0,
+      shutter_close: 0.0,
+      transparent_background: false,
+      pass_samples: 0,
+      tile_size: 32,
+      tile_order: TileOrder::default(),
+      crop: None,
+      crop_full_frame: false,
+      time_budget: None,
+      adaptive: None,
+      cancel: CancelToken::new(),
+      aovs: Vec::new(),
+      sampler: SamplerKind::Independent,
+      filter: Filter::default(),
+      seed: None,
+      stereo: None,
 
       image_height: 0,
-      pixel_samples_scale: 0.0,
-      center: Point3::new(),
-      pixel00_loc: Point3::new(),
-      pixel_delta_u: Vec3::new(),
-      pixel_delta_v: Vec3::new(),
-      u: Vec3::new(),
-      v: Vec3::new(),
-      w: Vec3::new(),
-      defocus_disk_u: Vec3::new(),
-      defocus_disk_v: Vec3::new(),
+      views: Vec::new(),
     }
   }
 
-  pub fn render<W: Write>(&mut self, world: &dyn Hittable, writer: &mut W) -> io::Result<()> {
+  /// Renders `world` into a new framebuffer, including any requested AOVs.
+  pub fn render(&mut self, world: &dyn Hittable) -> Framebuffer {
+    self.render_progressive(world, |_, _| {})
+  }
+
+  /// Renders in passes of `pass_samples` samples per pixel, calling
+  /// `on_pass` with the accumulated sums after each one so callers can
+  /// show or save intermediate images.
+  pub fn render_progressive<F>(&mut self, world: &dyn Hittable, on_pass: F) -> Framebuffer
+  where
+    F: FnMut(&PassInfo, &Accumulator) + Sync,
+  {
+    self.render_with(world, None, &mut PassCallback { on_pass, bar: ProgressBar::new() })
+  }
+
+  /// Continues a render from previously accumulated sums, e.g. a loaded
+  /// checkpoint, until every pixel has `samples_per_pixel` samples.
+  pub fn resume<F>(&mut self, world: &dyn Hittable, film: Accumulator, on_pass: F) -> Framebuffer
+  where
+    F: FnMut(&PassInfo, &Accumulator) + Sync,
+  {
+    self.render_with(world, Some(film), &mut PassCallback { on_pass, bar: ProgressBar::new() })
+  }
+
+  /// The full render loop: starts from `film` (which must match the camera's
+  /// image size) or from scratch, and reports every finished tile and pass
+  /// to `observer`. Panics if the crop window lies outside the image, which
+  /// `crop_bounds` reports up front.
+  pub fn render_with(
+    &mut self,
+    world: &dyn Hittable,
+    film: Option<Accumulator>,
+    observer: &mut dyn RenderObserver,
+  ) -> Framebuffer {
     self.initialize();
+    let (width, height) = self.film_size();
+    let mut film = film.unwrap_or_else(|| Accumulator::new(width, height, self.seed.unwrap_or_else(rand::random)));
+    assert_eq!((film.width, film.height), (width, height), "accumulator size does not match the camera");
+    if self.filter.splats() {
+      film.enable_splats();
+    }
 
-    writeln!(writer, "P3")?;
-    writeln!(writer, "{} {}", self.image_width, self.image_height)?;
-    writeln!(writer, "255")?;
-
-    let total_pixels = (self.image_width * self.image_height) as usize;
-    let pixel_colors: Vec<Color> = (0..total_pixels)
-      .into_par_iter()
-      .map(|pixel_idx| {
-        let j = pixel_idx as i32 / self.image_width;
-        let i = pixel_idx as i32 % self.image_width;
-        
-        if i == 0 {
-          eprint!("\rScanlines remaining: {} ", self.image_height - j);
+    let (x0, y0, x1, y1) = self.crop_bounds().expect("crop window lies outside the image");
+    let tiles = self.generate_tiles();
+    let start = Instant::now();
+    let pass_samples = if self.pass_samples > 0 {
+      self.pass_samples
+    } else if self.time_budget.is_some() || self.adaptive.is_some() {
+      DEFAULT_PASS_SAMPLES
+    } else {
+      self.samples_per_pixel
+    };
+    let region_samples = |film: &Accumulator| {
+      tiles.iter().flat_map(Tile::pixels).map(|(x, y)| film.pixels[y * width + x].samples).min().unwrap_or(0)
+    };
+    let deadline = self.time_budget.map(|budget| start + budget);
+    let stopped = || self.cancel.is_cancelled() || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+
+    // Adaptive renders spend the same total as a uniform one, spp times the
+    // pixel count, but only on pixels that have not converged yet.
+    let region_pixels: u64 = tiles.iter().map(|tile| tile.pixel_count() as u64).sum();
+    let budget = self.samples_per_pixel.max(0) as u64 * region_pixels;
+    let max_samples = self.adaptive.map_or(0, |adaptive| adaptive.max_samples(self.samples_per_pixel));
+
+    let spent = |film: &Accumulator| -> u64 {
+      tiles.iter().flat_map(Tile::pixels).map(|(x, y)| film.pixels[y * width + x].samples as u64).sum()
+    };
+    let progress = ProgressTracker::new(budget, spent(&film), self.time_budget);
+
+    let mut done = region_samples(&film) as i32;
+    let mut pass = 0;
+    while !stopped() {
+      match self.adaptive {
+        None => {
+          if done >= self.samples_per_pixel {
+            break;
+          }
+          let target = (done + pass_samples).min(self.samples_per_pixel) as u32;
+          let context = PassContext { tiles: &tiles, deadline, observer: &*observer, progress: &progress };
+          self.render_pass(world, &mut film, &|_, _| target, &context);
         }
+        Some(adaptive) => {
+          let finished = adaptive.finished_pixels(&film, max_samples, (x0, y0, x1, y1));
+          let region = || tiles.iter().flat_map(Tile::pixels).map(|(x, y)| y * width + x);
+          let spent = spent(&film);
+          let active = region().filter(|&index| !finished[index]).count() as u64;
+          if spent >= budget || active == 0 {
+            break;
+          }
+          // Never step past the remaining budget, shared by the active pixels.
+          let step = (pass_samples as u64).min((budget - spent).div_ceil(active)) as u32;
+          let target = |index: usize, sum: &PixelSum| {
+            if sum.samples < adaptive.min_samples {
+              adaptive.min_samples.min(max_samples)
+            } else if finished[index] {
+              sum.samples
+            } else {
+              (sum.samples + step).min(max_samples)
+            }
+          };
+          let context = PassContext { tiles: &tiles, deadline, observer: &*observer, progress: &progress };
+          self.render_pass(world, &mut film, &target, &context);
+        }
+      }
+      // A pass cut short by the deadline leaves some tiles behind, and
+      // adaptive passes skip converged pixels, so count what every pixel
+      // really has.
+      done = region_samples(&film) as i32;
+      pass += 1;
+
+      let info = PassInfo {
+        pass,
+        samples_per_pixel: done,
+        elapsed: start.elapsed(),
+        final_pass: (self.adaptive.is_none() && done >= self.samples_per_pixel) || stopped(),
+      };
+      observer.pass_done(&info, &film);
+    }
+    progress.finish();
+    observer.progress(&progress.report());
+
+    let mut framebuffer = film.resolve(self.transparent_background);
+    if !self.aovs.is_empty() {
+      let samples: Vec<AovSample> = (0..width * height)
+        .into_par_iter()
+        .map(|pixel_idx| {
+          let (i, j) = (pixel_idx % width, pixel_idx / width);
+          if (x0..x1).contains(&i) && (y0..y1).contains(&j) {
+            self.aov_sample(i as i32, j as i32, world)
+          } else {
+            AovSample::default()
+          }
+        })
+        .collect();
+      framebuffer.aovs = build_layers(&self.aovs, &samples, &framebuffer.sample_counts);
+    }
+    if !self.crop_full_frame {
+      framebuffer = framebuffer.crop(x0, y0, x1, y1);
+    }
+    framebuffer
+  }
+
+  /// Brings every pixel of `film` up to the sample count `target` picks for
+  /// it, one tile at a time. Tiles are handed to the rayon workers in scheduling order; each
+  /// renders into a copy of its pixels that is written back when it is done.
+  /// Filter splats are gathered per tile and added to the film once the pass
+  /// is over; their fixed-point sums come out the same on any number of
+  /// threads. Once the deadline passes or the render is cancelled, no further pixels
+  /// are started; the ones already sampled keep their (exact) counts.
+  fn render_pass(
+    &self,
+    world: &dyn Hittable,
+    film: &mut Accumulator,
+    target: &(dyn Fn(usize, &PixelSum) -> u32 + Sync),
+    context: &PassContext,
+  ) {
+    let seed = film.seed;
+    let width = film.width;
+    let film = Mutex::new(film);
+    let splat_tiles = Mutex::new(Vec::new());
+    let stopped = || self.cancel.is_cancelled() || context.deadline.is_some_and(|deadline| Instant::now() >= deadline);
+
+    context.tiles.iter().par_bridge().for_each(|tile| {
+      if stopped() {
+        return;
+      }
 
-        let mut pixel_color = Color::from_values(0.0, 0.0, 0.0);
-        for _sample in 0..self.samples_per_pixel {
-          let ray = self.get_ray(i, j);
-          pixel_color = pixel_color + self.ray_color(&ray, self.max_depth, world);
+      let mut sums: Vec<PixelSum> = {
+        let film = film.lock().unwrap();
+        tile.pixels().map(|(x, y)| film.pixels[y * width + x]).collect()
+      };
+      let mut work = TileWork {
+        seed,
+        sampler: sampler::create(self.sampler, self.samples_per_pixel, seed),
+        splats: self.filter.splats().then(|| SplatTile::new(tile, &self.filter, self.view_bounds(tile.x0, tile.y0))),
+      };
+      let mut samples = 0;
+      for ((x, y), sum) in tile.pixels().zip(sums.iter_mut()) {
+        if stopped() {
+          break;
         }
-        self.pixel_samples_scale * pixel_color
-      })
-      .collect();
+        let before = sum.samples;
+        self.render_pixel(world, (x, y), sum, target(y * width + x, sum), &mut work);
+        samples += (sum.samples - before) as u64;
+      }
+      {
+        let mut film = film.lock().unwrap();
+        for ((x, y), sum) in tile.pixels().zip(&sums) {
+          film.pixels[y * width + x] = *sum;
+        }
+      }
+      if let Some(splats) = work.splats {
+        splat_tiles.lock().unwrap().push(splats);
+      }
+      // The whole tile ran on this thread, so its ray count is all here.
+      context.progress.add(samples, RAYS_TRACED.with(|rays| rays.replace(0)));
+      context.observer.tile_done(tile, &sums);
+      context.observer.progress(&context.progress.report());
+    });
+
+    let film = film.into_inner().unwrap();
+    for splats in &splat_tiles.into_inner().unwrap() {
+      film.add_splats(splats);
+    }
+  }
 
-    for color in pixel_colors {
-      write_color(writer, &color)?;
+  /// Adds samples to pixel (x, y) until it has `target`. The generator is
+  /// reseeded for every sample from the film seed, the pixel and the sample
+  /// index, so a pixel's samples come out the same however they are split
+  /// into passes, tiles or threads, and resuming a saved film carries on
+  /// with the same sequence.
+  fn render_pixel(&self, world: &dyn Hittable, (x, y): (usize, usize), sum: &mut PixelSum, target: u32, work: &mut TileWork) {
+    let pixel_idx = (y * self.film_size().0 + x) as u64;
+    let sampler = work.sampler.as_mut();
+    while sum.samples < target {
+      seed_rng(sample_seed(work.seed, pixel_idx, sum.samples as u64));
+      sampler.start_sample(x, y, sum.samples);
+      let offset = self.sample_square(sampler);
+      let time = self.sample_time(sampler);
+      let (color, hit) = match self.get_ray(x as i32, y as i32, offset, time, sampler) {
+        Some(ray) => self.trace_camera_ray(&ray, world, sampler),
+        // Outside a fisheye's image circle.
+        None => (Color::new(), false),
+      };
+      sum.add_sample(color, hit);
+      if let Some(splats) = &mut work.splats {
+        splats.add(&self.filter, x as f64 + offset.x(), y as f64 + offset.y(), color, hit);
+      }
     }
+  }
 
-    eprintln!("\rDone.                ");
-    Ok(())
+  /// Image height for the current settings.
+  pub fn image_height(&self) -> i32 {
+    let aspect_ratio = self.projection.aspect_ratio().unwrap_or(self.aspect_ratio);
+    ((self.image_width as f64 / aspect_ratio) as i32).max(1)
   }
 
-  fn initialize(&mut self) {
-    self.image_height = (self.image_width as f64 / self.aspect_ratio) as i32;
-    self.image_height = if self.image_height < 1 {1} else { self.image_height };
+  /// Size of the rendered image: the image size, or twice it along one
+  /// axis for a stereo pair.
+  pub fn film_size(&self) -> (usize, usize) {
+    let (width, height) = (self.image_width as usize, self.image_height() as usize);
+    match self.stereo.map(|stereo| stereo.layout) {
+      None => (width, height),
+      Some(StereoLayout::SideBySide) => (2 * width, height),
+      Some(StereoLayout::TopBottom) => (width, 2 * height),
+    }
+  }
 
-    self.pixel_samples_scale = 1.0 / self.samples_per_pixel as f64;
+  /// Pixel region being rendered as `(x0, y0, x1, y1)`, ends exclusive:
+  /// the crop window, or the whole image without one. `None` when the crop
+  /// window lies entirely outside the image.
+  pub fn crop_bounds(&self) -> Option<(usize, usize, usize, usize)> {
+    let (width, height) = self.film_size();
+    match &self.crop {
+      Some(crop) => crop.pixel_bounds(width, height),
+      None => Some((0, 0, width, height)),
+    }
+  }
 
-    self.center = self.lookfrom;
+  fn initialize(&mut self) {
+    self.image_height = self.image_height();
+
+    let view = self.view(self.lookfrom, self.lookat, self.focus_dist);
+    self.views = match self.stereo {
+      None => vec![view],
+      Some(stereo) => {
+        let half = stereo.interocular_distance(self.focus_dist) / 2.0;
+        [-half, half]
+          .into_iter()
+          .map(|offset| {
+            if self.projection.is_panoramic() {
+              // Omni-directional stereo: the eyes turn with the direction
+              // being looked at, so they are placed per ray.
+              return View { eye_offset: offset, ..view };
+            }
+            let eye = view.center + offset * view.u;
+            match stereo.convergence {
+              // Shifting the eye but keeping the shared viewport on the focus
+              // plane skews each frustum towards the middle.
+              Convergence::OffAxis => View { center: eye, ..view },
+              Convergence::ToeIn => {
+                let target = view.center - self.focus_dist * view.w;
+                self.view(eye, target, (target - eye).length())
+              }
+            }
+          })
+          .collect()
+      }
+    };
+  }
 
+  /// Frame for rays leaving `lookfrom` towards `lookat`, with the focus
+  /// plane `focus_dist` away.
+  fn view(&self, lookfrom: Point3, lookat: Point3, focus_dist: f64) -> View {
     // let focal_length = (self.lookfrom - self.lookat).length();
     let theta = degrees_to_radians(self.vfov);
     let h = (theta / 2.0).tan();
-    let viewport_height = 2.0 * h * self.focus_dist;
-    let viewport_width = viewport_height * (self.image_width as f64 / self.image_height as f64);
-
-    self.w = unit_vector(&(self.lookfrom - self.lookat));
-    self.u = unit_vector(&cross(&self.vup, &self.w));
-    self.v = cross(&self.w, &self.u);
+    let mut viewport_height = 2.0 * h * focus_dist;
+    let mut viewport_width = viewport_height * (self.image_width as f64 / self.image_height as f64);
+    if let Projection::Orthographic { view_width } = self.projection
+      && view_width > 0.0
+    {
+      viewport_height *= view_width / viewport_width;
+      viewport_width = view_width;
+    }
 
-    let viewport_u = viewport_width * self.u;
-    let viewport_v = viewport_height * (-self.v);
+    let w = unit_vector(&(lookfrom - lookat));
+    let u = unit_vector(&cross(&self.vup, &w));
+    let v = cross(&w, &u);
+
+    let viewport_u = viewport_width * u;
+    let viewport_v = viewport_height * (-v);
+
+    let pixel_delta_u = viewport_u / self.image_width as f64;
+    let pixel_delta_v = viewport_v / self.image_height as f64;
+
+    let viewport_upper_left = lookfrom - (focus_dist * w) - viewport_u / 2.0 - viewport_v / 2.0;
+    let pixel00_loc = viewport_upper_left + 0.5 * (pixel_delta_u + pixel_delta_v);
+
+    let defocus_radius = focus_dist * (degrees_to_radians(self.defocus_angle / 2.0)).tan();
+    View {
+      center: lookfrom,
+      pixel00_loc,
+      pixel_delta_u,
+      pixel_delta_v,
+      u,
+      v,
+      w,
+      defocus_disk_u: u * defocus_radius,
+      defocus_disk_v: v * defocus_radius,
+      focus_dist,
+      eye_offset: 0.0,
+    }
+  }
 
-    self.pixel_delta_u = viewport_u / self.image_width as f64;
-    self.pixel_delta_v = viewport_v / self.image_height as f64;
+  /// The view film pixel (x, y) belongs to, and the pixel's position within
+  /// that view. The left eye of a stereo pair comes first.
+  fn locate(&self, x: usize, y: usize) -> (&View, usize, usize) {
+    let (width, height) = (self.image_width as usize, self.image_height as usize);
+    match self.stereo.map(|stereo| stereo.layout) {
+      Some(StereoLayout::SideBySide) if x >= width => (&self.views[1], x - width, y),
+      Some(StereoLayout::TopBottom) if y >= height => (&self.views[1], x, y - height),
+      _ => (&self.views[0], x, y),
+    }
+  }
 
-    let viewport_upper_left = self.center - (self.focus_dist * self.w) - viewport_u / 2.0 - viewport_v / 2.0;
-    self.pixel00_loc = viewport_upper_left + 0.5 * (self.pixel_delta_u + self.pixel_delta_v);
+  /// Pixel bounds `(x0, y0, x1, y1)` of the view film pixel (x, y) belongs
+  /// to, which filters must not reach across.
+  fn view_bounds(&self, x: usize, y: usize) -> (usize, usize, usize, usize) {
+    let (width, height) = (self.image_width as usize, self.image_height as usize);
+    match self.stereo.map(|stereo| stereo.layout) {
+      None => (0, 0, width, height),
+      Some(StereoLayout::SideBySide) => {
+        let x0 = if x >= width { width } else { 0 };
+        (x0, 0, x0 + width, height)
+      }
+      Some(StereoLayout::TopBottom) => {
+        let y0 = if y >= height { height } else { 0 };
+        (0, y0, width, y0 + height)
+      }
+    }
+  }
 
-    let defocus_radius = self.focus_dist * (degrees_to_radians(self.defocus_angle / 2.0)).tan();
-    self.defocus_disk_u = self.u * defocus_radius;
-    self.defocus_disk_v = self.v * defocus_radius;
+  /// Tiles covering the crop region, split at the seam between the eyes of
+  /// a stereo pair so that no tile holds pixels of both.
+  fn generate_tiles(&self) -> Vec<Tile> {
+    let (x0, y0, x1, y1) = self.crop_bounds().expect("crop window lies outside the image");
+    let (width, height) = (self.image_width as usize, self.image_height as usize);
+    let views = match self.stereo.map(|stereo| stereo.layout) {
+      None => vec![(0, 0)],
+      Some(StereoLayout::SideBySide) => vec![(0, 0), (width, 0)],
+      Some(StereoLayout::TopBottom) => vec![(0, 0), (0, height)],
+    };
+    let mut tiles = Vec::new();
+    for (vx, vy) in views {
+      let (tx0, ty0) = (x0.max(vx), y0.max(vy));
+      let (tx1, ty1) = (x1.min(vx + width), y1.min(vy + height));
+      if tx0 < tx1 && ty0 < ty1 {
+        tiles.extend(tiles::generate_in(tx0, ty0, tx1, ty1, self.tile_size, self.tile_order));
+      }
+    }
+    for (index, tile) in tiles.iter_mut().enumerate() {
+      tile.index = index;
+    }
+    tiles
   }
 
-  fn get_ray(&self, i: i32, j: i32) -> Ray {
-    let offset = self.sample_square();
-    let pixel_sample = self.pixel00_loc
-      + ((i as f64 + offset.x()) * self.pixel_delta_u)
-      + ((j as f64 + offset.y()) * self.pixel_delta_v);
+  /// Ray through film pixel (i, j) at `time`, `offset` from its centre.
+  fn get_ray(&self, i: i32, j: i32, offset: Vec3, time: f64, sampler: &mut dyn Sampler) -> Option<Ray> {
+    let (view, i, j) = self.locate(i as usize, j as usize);
+    if self.projection.is_panoramic() {
+      return self.panoramic_ray(view, i as f64 + offset.x(), j as f64 + offset.y(), time);
+    }
+    let pixel_sample = view.pixel00_loc
+      + ((i as f64 + offset.x()) * view.pixel_delta_u)
+      + ((j as f64 + offset.y()) * view.pixel_delta_v);
 
+    let lens_center = self.lens_center(view, pixel_sample);
     let ray_origin = if self.defocus_angle <= 0.0 {
-      self.center
+      lens_center
     } else {
-      self.defocus_disk_sample()
+      self.defocus_disk_sample(view, lens_center, sampler)
     };
     let ray_direction = pixel_sample - ray_origin;
 
-    Ray::from_origin_direction(ray_origin, ray_direction)
+    Some(Ray::from_origin_direction_time(ray_origin, ray_direction, time))
   }
 
-  fn sample_square(&self) -> Vec3 {
-    Vec3::from_values(random_float() - 0.5, random_float() - 0.5, 0.0)
+  /// Ray from the camera centre through position (x, y) of `view`, in
+  /// pixels with pixel centres on integers, for a panoramic projection.
+  /// Stereo eyes sit on a circle around the centre, side on to the ray.
+  fn panoramic_ray(&self, view: &View, x: f64, y: f64, time: f64) -> Option<Ray> {
+    let s = (x + 0.5) / self.image_width as f64;
+    let t = (y + 0.5) / self.image_height as f64;
+    let d = self.projection.direction(s, t)?;
+    let direction = d.x() * view.u + d.y() * view.v - d.z() * view.w;
+    let azimuth = d.x().atan2(d.z());
+    let origin = view.center + view.eye_offset * (azimuth.cos() * view.u + azimuth.sin() * view.w);
+    Some(Ray::from_origin_direction_time(origin, direction, time))
   }
 
-  fn defocus_disk_sample(&self) -> Point3 {
-    let p = random_in_unit_disk();
-    self.center + (p.x() * self.defocus_disk_u) + (p.y() * self.defocus_disk_v)
+  /// AOVs come from a single pinhole ray through the pixel centre at the
+  /// middle of the shutter interval, so IDs and depths are crisp and
+  /// identical between runs.
+  fn aov_sample(&self, i: i32, j: i32, world: &dyn Hittable) -> AovSample {
+    let (view, i, j) = self.locate(i as usize, j as usize);
+    let time = 0.5 * (self.shutter_open + self.shutter_close);
+    // Panoramic views measure depth along the ray rather than the view axis.
+    let (ray, view_dir) = if self.projection.is_panoramic() {
+      let Some(ray) = self.panoramic_ray(view, i as f64, j as f64, time) else {
+        return AovSample::default();
+      };
+      let view_dir = unit_vector(ray.direction());
+      (ray, view_dir)
+    } else {
+      let pixel_center = view.pixel00_loc + (i as f64 * view.pixel_delta_u) + (j as f64 * view.pixel_delta_v);
+      let lens_center = self.lens_center(view, pixel_center);
+      (Ray::from_origin_direction_time(lens_center, pixel_center - lens_center, time), -view.w)
+    };
+
+    let mut rec = HitRecord::new();
+    if world.hit(&ray, Interval::from_range(0.001, INFINITY), &mut rec) {
+      AovSample::from_hit(&rec, *ray.origin(), view_dir)
+    } else {
+      AovSample::default()
+    }
   }
 
-  fn ray_color(&self, ray: &Ray, depth: i32, world: &dyn Hittable) -> Color {
+  /// Where rays towards `focus_point` on the focus plane leave the lens:
+  /// the view centre for perspective views, and the point of the lens
+  /// plane straight behind it for orthographic ones.
+  fn lens_center(&self, view: &View, focus_point: Point3) -> Point3 {
+    match self.projection {
+      Projection::Orthographic { .. } => focus_point + view.focus_dist * view.w,
+      _ => view.center,
+    }
+  }
+
+  fn sample_square(&self, sampler: &mut dyn Sampler) -> Vec3 {
+    let (u, v) = sampler.get_2d();
+    Vec3::from_values(u - 0.5, v - 0.5, 0.0)
+  }
+
+  /// Moment within the shutter interval for a camera ray. A closed-down
+  /// shutter draws nothing, so still renders use the same sample dimensions
+  /// as before.
+  fn sample_time(&self, sampler: &mut dyn Sampler) -> f64 {
+    if self.shutter_close <= self.shutter_open {
+      return self.shutter_open;
+    }
+    self.shutter_open + sampler.get_1d() * (self.shutter_close - self.shutter_open)
+  }
+
+  fn defocus_disk_sample(&self, view: &View, lens_center: Point3, sampler: &mut dyn Sampler) -> Point3 {
+    let (u, v) = sampler.get_2d();
+    let p = sample_unit_disk(u, v);
+    lens_center + (p.x() * view.defocus_disk_u) + (p.y() * view.defocus_disk_v)
+  }
+
+  /// Traces a ray from the camera, also reporting whether it hit anything.
+  /// With a transparent background, misses contribute no radiance.
+  fn trace_camera_ray(&self, ray: &Ray, world: &dyn Hittable, sampler: &mut dyn Sampler) -> (Color, bool) {
+    if self.max_depth <= 0 {
+      return (Color::new(), false);
+    }
+    RAYS_TRACED.with(|rays| rays.set(rays.get() + 1));
+    let mut rec = HitRecord::new();
+    if world.hit(ray, Interval::from_range(0.001, INFINITY), &mut rec) {
+      (self.shade(ray, &rec, self.max_depth, world, sampler), true)
+    } else if self.transparent_background {
+      (Color::new(), false)
+    } else {
+      (self.background(ray), false)
+    }
+  }
+
+  fn ray_color(&self, ray: &Ray, depth: i32, world: &dyn Hittable, sampler: &mut dyn Sampler) -> Color {
     if depth <= 0{
       return Color::from_values(0.0, 0.0, 0.0);
     }
+    RAYS_TRACED.with(|rays| rays.set(rays.get() + 1));
     let mut rec = HitRecord::new();
 
     if world.hit(ray, Interval::from_range(0.001, INFINITY), &mut rec) {
-      // let direction = random_on_hemisphere(&rec.normal);
-      let mut scattered = Ray::new();
-      let mut attenuation = Color::new();
-      // let direction = rec.normal + random_unit_vector();
-      // return 0.9 * self.ray_color(&Ray::from_origin_direction(rec.p, direction),depth - 1, world);
-
-      if let Some(material) = &rec.mat {
-        if material.scatter(ray, &rec, &mut attenuation, &mut scattered) {
-          return attenuation * self.ray_color(&scattered, depth - 1, world);
-        }
-      }
-      return Color::from_values(0.0, 0.0, 0.0)
+      return self.shade(ray, &rec, depth, world, sampler);
+    }
+
+    self.background(ray)
+  }
+
+  fn shade(&self, ray: &Ray, rec: &HitRecord, depth: i32, world: &dyn Hittable, sampler: &mut dyn Sampler) -> Color {
+    // let direction = random_on_hemisphere(&rec.normal);
+    let mut scattered = Ray::new();
+    let mut attenuation = Color::new();
+    // let direction = rec.normal + random_unit_vector();
+    // return 0.9 * self.ray_color(&Ray::from_origin_direction(rec.p, direction),depth - 1, world);
+
+    if let Some(material) = &rec.mat
+      && material.scatter(ray, rec, &mut attenuation, &mut scattered, sampler) {
+      return attenuation * self.ray_color(&scattered, depth - 1, world, sampler);
     }
+    Color::from_values(0.0, 0.0, 0.0)
+  }
 
+  fn background(&self, ray: &Ray) -> Color {
     let unit_direction = unit_vector(ray.direction());
     let a = 0.5 * (unit_direction.y() + 1.0);
     (1.0 - a) * Color::from_values(1.0, 1.0, 1.0) + a * Color::from_values(0.5, 0.7, 1.0)
   }
 }
 
+/// Where a progressive render stands after a pass.
+#[derive(Debug, Clone, Copy)]
+pub struct PassInfo {
+  /// 1-based pass number.
+  pub pass: usize,
+  /// Samples per pixel accumulated so far.
+  pub samples_per_pixel: i32,
+  pub elapsed: Duration,
+  /// The last pass; the image returned by the render follows.
+  pub final_pass: bool,
+}
+
+/// A rectangular part of the image, ends exclusive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CropWindow {
+  Pixels { x0: usize, y0: usize, x1: usize, y1: usize },
+  /// Fractions of the image width and height, `0.0..=1.0`.
+  Normalized { x0: f64, y0: f64, x1: f64, y1: f64 },
+}
+
+impl CropWindow {
+  /// The window in pixels, clamped to the image and at least one pixel big,
+  /// or `None` if it starts beyond the image. Normalized windows are widened
+  /// to whole pixels.
+  pub fn pixel_bounds(&self, width: usize, height: usize) -> Option<(usize, usize, usize, usize)> {
+    let (x0, y0, x1, y1) = match *self {
+      CropWindow::Pixels { x0, y0, x1, y1 } => (x0, y0, x1, y1),
+      CropWindow::Normalized { x0, y0, x1, y1 } => (
+        (x0.clamp(0.0, 1.0) * width as f64).floor() as usize,
+        (y0.clamp(0.0, 1.0) * height as f64).floor() as usize,
+        (x1.clamp(0.0, 1.0) * width as f64).ceil() as usize,
+        (y1.clamp(0.0, 1.0) * height as f64).ceil() as usize,
+      ),
+    };
+    if x0 >= width || y0 >= height {
+      return None;
+    }
+    Some((x0, y0, x1.clamp(x0 + 1, width), y1.clamp(y0 + 1, height)))
+  }
+}
+
+/// Settings for variance-driven adaptive sampling.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveSampling {
+  /// Samples every pixel gets before its variance is trusted.
+  pub min_samples: u32,
+  /// Per-pixel cap; 0 means four times `samples_per_pixel`.
+  pub max_samples: u32,
+  /// A pixel is converged once `PixelSum::relative_error` is below this.
+  pub threshold: f64,
+}
+
+impl AdaptiveSampling {
+  pub fn new() -> Self {
+    AdaptiveSampling { min_samples: 16, max_samples: 0, threshold: 0.02 }
+  }
+
+  fn max_samples(&self, samples_per_pixel: i32) -> u32 {
+    if self.max_samples > 0 {
+      self.max_samples
+    } else {
+      (samples_per_pixel.max(1) as u32).saturating_mul(4)
+    }
+  }
+
+  /// Which pixels need no more samples: those at the cap, and those whose
+  /// whole 3x3 neighbourhood is below the error threshold. Looking at the
+  /// neighbours keeps a pixel that happened to miss a rare bright path (and
+  /// so shows no variance yet) from stopping next to ones that found it.
+  /// Only neighbours within `bounds`, the region being rendered, count;
+  /// pixels outside a crop window never get any samples.
+  fn finished_pixels(&self, film: &Accumulator, max_samples: u32, bounds: (usize, usize, usize, usize)) -> Vec<bool> {
+    let (width, height) = (film.width, film.height);
+    let (x0, y0, x1, y1) = bounds;
+    let converged: Vec<bool> = film
+      .pixels
+      .iter()
+      .map(|sum| sum.samples >= self.min_samples && sum.relative_error() < self.threshold)
+      .collect();
+
+    (0..width * height)
+      .map(|index| {
+        if film.pixels[index].samples >= max_samples {
+          return true;
+        }
+        let (x, y) = (index % width, index / width);
+        (y.saturating_sub(1).max(y0)..(y + 2).min(y1))
+          .all(|ny| (x.saturating_sub(1).max(x0)..(x + 2).min(x1)).all(|nx| converged[ny * width + nx]))
+      })
+      .collect()
+  }
+}
+
+impl Default for AdaptiveSampling {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Hooks into a running render. Tile callbacks arrive on the worker threads
+/// as each tile finishes; pass callbacks arrive on the rendering thread
+/// once every tile of the pass is done.
+pub trait RenderObserver: Sync {
+  /// `pixels` are the tile's updated sums, row by row.
+  fn tile_done(&self, _tile: &Tile, _pixels: &[PixelSum]) {}
+
+  fn pass_done(&mut self, _pass: &PassInfo, _film: &Accumulator) {}
+
+  /// Called from the worker threads after every tile, and once more with
+  /// `finished` set when the render stops.
+  fn progress(&self, _progress: &Progress) {}
+}
+
+/// Adapts a pass closure to `RenderObserver`, drawing a progress bar on
+/// stderr in between.
+struct PassCallback<F> {
+  on_pass: F,
+  bar: ProgressBar,
+}
+
+impl<F> RenderObserver for PassCallback<F>
+where
+  F: FnMut(&PassInfo, &Accumulator) + Sync,
+{
+  fn pass_done(&mut self, pass: &PassInfo, film: &Accumulator) {
+    // Whatever the callback prints starts on a clean line.
+    self.bar.clear();
+    (self.on_pass)(pass, film)
+  }
+
+  fn progress(&self, progress: &Progress) {
+    self.bar.update(progress);
+  }
+}
+
+/// A worker's state while it renders one tile.
+struct TileWork {
+  seed: u64,
+  sampler: Box<dyn Sampler>,
+  /// Filter splats, for filters that reach beyond the pixel.
+  splats: Option<SplatTile>,
+}
+
+/// What the workers of one pass share.
+struct PassContext<'a> {
+  tiles: &'a [Tile],
+  deadline: Option<Instant>,
+  observer: &'a dyn RenderObserver,
+  progress: &'a ProgressTracker,
+}
+
 impl Default for Camera {
   fn default() -> Self {
     Self::new()
   }
 }
 
+/// Optional replacements for the public `Camera` settings, applied on top of
+/// whatever a scene configured.
+#[derive(Debug, Clone, Default)]
+pub struct CameraOverrides {
+  pub aspect_ratio: Option<f64>,
+  pub image_width: Option<i32>,
+  pub samples_per_pixel: Option<i32>,
+  pub max_depth: Option<i32>,
+  pub vfov: Option<f64>,
+  pub projection: Option<Projection>,
+  pub lookfrom: Option<Point3>,
+  pub lookat: Option<Point3>,
+  pub vup: Option<Vec3>,
+  pub defocus_angle: Option<f64>,
+  pub focus_dist: Option<f64>,
+  pub shutter_open: Option<f64>,
+  pub shutter_close: Option<f64>,
+  pub pass_samples: Option<i32>,
+  pub tile_size: Option<usize>,
+  pub tile_order: Option<TileOrder>,
+  pub crop: Option<CropWindow>,
+  pub crop_full_frame: Option<bool>,
+  pub time_budget: Option<Duration>,
+  pub adaptive: Option<AdaptiveSampling>,
+  pub sampler: Option<SamplerKind>,
+  pub filter: Option<Filter>,
+  pub stereo: Option<Stereo>,
+  pub aovs: Option<Vec<Aov>>,
+}
+
+impl CameraOverrides {
+  pub fn apply(&self, camera: &mut Camera) {
+    if let Some(aspect_ratio) = self.aspect_ratio {
+      camera.aspect_ratio = aspect_ratio;
+    }
+    if let Some(image_width) = self.image_width {
+      camera.image_width = image_width;
+    }
+    if let Some(samples_per_pixel) = self.samples_per_pixel {
+      camera.samples_per_pixel = samples_per_pixel;
+    }
+    if let Some(max_depth) = self.max_depth {
+      camera.max_depth = max_depth;
+    }
+    if let Some(vfov) = self.vfov {
+      camera.vfov = vfov;
+    }
+    if let Some(projection) = self.projection {
+      camera.projection = projection;
+    }
+    if let Some(lookfrom) = self.lookfrom {
+      camera.lookfrom = lookfrom;
+    }
+    if let Some(lookat) = self.lookat {
+      camera.lookat = lookat;
+    }
+    if let Some(vup) = self.vup {
+      camera.vup = vup;
+    }
+    if let Some(defocus_angle) = self.defocus_angle {
+      camera.defocus_angle = defocus_angle;
+    }
+    if let Some(focus_dist) = self.focus_dist {
+      camera.focus_dist = focus_dist;
+    }
+    if let Some(shutter_open) = self.shutter_open {
+      camera.shutter_open = shutter_open;
+    }
+    if let Some(shutter_close) = self.shutter_close {
+      camera.shutter_close = shutter_close;
+    }
+    if let Some(pass_samples) = self.pass_samples {
+      camera.pass_samples = pass_samples;
+    }
+    if let Some(tile_size) = self.tile_size {
+      camera.tile_size = tile_size;
+    }
+    if let Some(tile_order) = self.tile_order {
+      camera.tile_order = tile_order;
+    }
+    if let Some(crop) = self.crop {
+      camera.crop = Some(crop);
+    }
+    if let Some(crop_full_frame) = self.crop_full_frame {
+      camera.crop_full_frame = crop_full_frame;
+    }
+    if let Some(time_budget) = self.time_budget {
+      camera.time_budget = Some(time_budget);
+    }
+    if let Some(adaptive) = self.adaptive {
+      camera.adaptive = Some(adaptive);
+    }
+    if let Some(sampler) = self.sampler {
+      camera.sampler = sampler;
+    }
+    if let Some(filter) = self.filter {
+      camera.filter = filter;
+    }
+    if let Some(stereo) = self.stereo {
+      camera.stereo = Some(stereo);
+    }
+    if let Some(aovs) = &self.aovs {
+      camera.aovs = aovs.clone();
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::filter::FilterKind;
+  use crate::hittable_list::HittableList;
+  use crate::material::{Dielectric, Lambertian, Material, Metal};
+  use crate::sphere::Sphere;
+  use std::sync::Arc;
+
+  #[test]
+  fn adaptive_sampling_finishes_crop_edges() {
+    let mut camera = Camera::new();
+    camera.image_width = 32;
+    camera.samples_per_pixel = 32;
+    camera.seed = Some(1);
+    camera.crop = Some(CropWindow::Pixels { x0: 8, y0: 8, x1: 24, y1: 24 });
+    camera.adaptive = Some(AdaptiveSampling { min_samples: 16, max_samples: 64, threshold: 0.05 });
+
+    // The sky alone converges at the minimum everywhere, the crop's edge
+    // pixels included.
+    let image = camera.render_with(&HittableList::_new(), None, &mut NoObserver);
+    assert_eq!((image.width, image.height), (16, 16));
+    assert!(image.sample_counts.iter().all(|&samples| samples == 16), "{:?}", image.sample_counts);
+  }
+
+  #[test]
+  fn output_is_identical_across_threads_tiles_and_passes() {
+    let mut world = HittableList::_new();
+    let sphere = |x: f64, y: f64, radius: f64, material: Arc<dyn Material>| {
+      Arc::new(Sphere::new(Point3::from_values(x, y, -3.0), radius, material))
+    };
+    world._add(sphere(0.0, -100.5, 100.0, Arc::new(Lambertian::new(Color::from_values(0.5, 0.6, 0.3)))));
+    world._add(sphere(-1.0, 0.0, 0.5, Arc::new(Lambertian::new(Color::from_values(0.7, 0.2, 0.2)))));
+    world._add(sphere(0.0, 0.0, 0.5, Arc::new(Dielectric::new(1.5))));
+    world._add(sphere(1.0, 0.0, 0.5, Arc::new(Metal::new(Color::from_values(0.8, 0.8, 0.8), 0.3))));
+
+    let render = |threads: usize, tile_order: TileOrder, tile_size: usize, pass_samples: i32| {
+      let mut camera = Camera::new();
+      camera.aspect_ratio = 16.0 / 9.0;
+      camera.image_width = 37;
+      camera.samples_per_pixel = 6;
+      camera.pass_samples = pass_samples;
+      camera.seed = Some(3);
+      // Splatting filters add across tile borders and passes, the likeliest
+      // place for scheduling to leak into the sums.
+      camera.filter = Filter::new(FilterKind::Mitchell);
+      camera.tile_order = tile_order;
+      camera.tile_size = tile_size;
+      let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+      pool.install(|| camera.render_with(&world, None, &mut NoObserver))
+    };
+
+    let single = render(1, TileOrder::Scanline, 8, 0);
+    let parallel = render(4, TileOrder::Hilbert, 5, 4);
+    assert!(single.pixels.iter().map(|c| c.length()).sum::<f64>() > 0.0);
+    assert!(single.pixels == parallel.pixels, "radiance depends on scheduling");
+    assert_eq!(single.alpha, parallel.alpha);
+    assert_eq!(single.sample_counts, parallel.sample_counts);
+  }
+
+  struct NoObserver;
+
+  impl RenderObserver for NoObserver {}
+}
@@ -0,0 +1,33 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Shared flag that asks a running render to stop. Clones refer to the same
+/// flag, so one can be handed to the camera and another kept to cancel with.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+  flag: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+  pub fn new() -> Self {
+    CancelToken::default()
+  }
+
+  pub fn cancel(&self) {
+    self.flag.store(true, Ordering::SeqCst);
+  }
+
+  /// Clears a cancellation so the token can be used for another render.
+  pub fn reset(&self) {
+    self.flag.store(false, Ordering::SeqCst);
+  }
+
+  pub fn is_cancelled(&self) -> bool {
+    self.flag.load(Ordering::Relaxed)
+  }
+
+  /// The underlying flag, e.g. for registering with a signal handler.
+  pub fn flag(&self) -> &Arc<AtomicBool> {
+    &self.flag
+  }
+}
@@ -0,0 +1,457 @@
+use crate::aov::Aov;
+use crate::camera::{AdaptiveSampling, Camera, CropWindow};
+use crate::sampler::SamplerKind;
+use crate::filter::{Filter, FilterKind, Splat};
+use crate::framebuffer::{Accumulator, PixelSum};
+use crate::projection::{FisheyeMapping, Projection};
+use crate::stereo::{Convergence, Stereo, StereoLayout};
+use crate::vec3::{Point3, Vec3};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::time::Duration;
+
+const MAGIC: &[u8; 8] = b"RTCKPT\0\0";
+const VERSION: u32 = 11;
+
+/// A render saved part-way through: what was being rendered, how the world
+/// was generated, and every sample accumulated so far.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+  pub scene: String,
+  /// Seed the scene layout was generated from, so the same world is rebuilt.
+  pub layout_seed: u64,
+  pub camera: CameraSettings,
+  pub film: Accumulator,
+}
+
+/// The camera settings a checkpoint needs to reproduce the same rays.
+#[derive(Debug, Clone)]
+pub struct CameraSettings {
+  pub aspect_ratio: f64,
+  pub image_width: i32,
+  pub samples_per_pixel: i32,
+  pub max_depth: i32,
+  pub vfov: f64,
+  pub projection: Projection,
+  pub lookfrom: Point3,
+  pub lookat: Point3,
+  pub vup: Vec3,
+  pub defocus_angle: f64,
+  pub focus_dist: f64,
+  pub shutter_open: f64,
+  pub shutter_close: f64,
+  pub pass_samples: i32,
+  pub crop: Option<CropWindow>,
+  pub crop_full_frame: bool,
+  pub time_budget: Option<Duration>,
+  pub adaptive: Option<AdaptiveSampling>,
+  pub sampler: SamplerKind,
+  pub filter: Filter,
+  pub stereo: Option<Stereo>,
+  /// Whether sky samples were accumulated as transparent; a resume has to
+  /// write to a format with the same alpha handling.
+  pub transparent_background: bool,
+  pub aovs: Vec<Aov>,
+}
+
+impl CameraSettings {
+  pub fn capture(camera: &Camera) -> Self {
+    CameraSettings {
+      aspect_ratio: camera.aspect_ratio,
+      image_width: camera.image_width,
+      samples_per_pixel: camera.samples_per_pixel,
+      max_depth: camera.max_depth,
+      vfov: camera.vfov,
+      projection: camera.projection,
+      lookfrom: camera.lookfrom,
+      lookat: camera.lookat,
+      vup: camera.vup,
+      defocus_angle: camera.defocus_angle,
+      focus_dist: camera.focus_dist,
+      shutter_open: camera.shutter_open,
+      shutter_close: camera.shutter_close,
+      pass_samples: camera.pass_samples,
+      crop: camera.crop,
+      crop_full_frame: camera.crop_full_frame,
+      time_budget: camera.time_budget,
+      adaptive: camera.adaptive,
+      sampler: camera.sampler,
+      filter: camera.filter,
+      stereo: camera.stereo,
+      transparent_background: camera.transparent_background,
+      aovs: camera.aovs.clone(),
+    }
+  }
+
+  pub fn apply(&self, camera: &mut Camera) {
+    camera.aspect_ratio = self.aspect_ratio;
+    camera.image_width = self.image_width;
+    camera.samples_per_pixel = self.samples_per_pixel;
+    camera.max_depth = self.max_depth;
+    camera.vfov = self.vfov;
+    camera.projection = self.projection;
+    camera.lookfrom = self.lookfrom;
+    camera.lookat = self.lookat;
+    camera.vup = self.vup;
+    camera.defocus_angle = self.defocus_angle;
+    camera.focus_dist = self.focus_dist;
+    camera.shutter_open = self.shutter_open;
+    camera.shutter_close = self.shutter_close;
+    camera.pass_samples = self.pass_samples;
+    camera.crop = self.crop;
+    camera.crop_full_frame = self.crop_full_frame;
+    camera.time_budget = self.time_budget;
+    camera.adaptive = self.adaptive;
+    camera.sampler = self.sampler;
+    camera.filter = self.filter;
+    camera.stereo = self.stereo;
+    camera.transparent_background = self.transparent_background;
+    camera.aovs = self.aovs.clone();
+  }
+}
+
+/// Writes a checkpoint next to `path` first and then renames it into place,
+/// so a render killed while saving never leaves a truncated file behind.
+pub fn save_checkpoint<P: AsRef<Path>>(
+  path: P,
+  scene: &str,
+  layout_seed: u64,
+  camera: &CameraSettings,
+  film: &Accumulator,
+) -> io::Result<()> {
+  let path = path.as_ref();
+  let mut temp = path.as_os_str().to_owned();
+  temp.push(".tmp");
+
+  let mut out = BufWriter::new(File::create(&temp)?);
+  write_checkpoint(&mut out, scene, layout_seed, camera, film)?;
+  out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+  fs::rename(&temp, path)
+}
+
+pub fn load_checkpoint<P: AsRef<Path>>(path: P) -> io::Result<Checkpoint> {
+  read_checkpoint(&mut BufReader::new(File::open(path)?))
+}
+
+/// Little-endian binary layout: header, settings, then one record per pixel.
+pub fn write_checkpoint<W: Write>(
+  out: &mut W,
+  scene: &str,
+  layout_seed: u64,
+  camera: &CameraSettings,
+  film: &Accumulator,
+) -> io::Result<()> {
+  out.write_all(MAGIC)?;
+  out.write_all(&VERSION.to_le_bytes())?;
+  write_u32(out, scene.len() as u32)?;
+  out.write_all(scene.as_bytes())?;
+  write_u64(out, layout_seed)?;
+
+  write_f64(out, camera.aspect_ratio)?;
+  write_u32(out, camera.image_width as u32)?;
+  write_u32(out, camera.samples_per_pixel as u32)?;
+  write_u32(out, camera.max_depth as u32)?;
+  write_f64(out, camera.vfov)?;
+  write_projection(out, &camera.projection)?;
+  write_vec3(out, camera.lookfrom)?;
+  write_vec3(out, camera.lookat)?;
+  write_vec3(out, camera.vup)?;
+  write_f64(out, camera.defocus_angle)?;
+  write_f64(out, camera.focus_dist)?;
+  write_f64(out, camera.shutter_open)?;
+  write_f64(out, camera.shutter_close)?;
+  write_u32(out, camera.pass_samples as u32)?;
+  match camera.crop {
+    None => write_u32(out, 0)?,
+    Some(CropWindow::Pixels { x0, y0, x1, y1 }) => {
+      write_u32(out, 1)?;
+      for value in [x0, y0, x1, y1] {
+        write_u64(out, value as u64)?;
+      }
+    }
+    Some(CropWindow::Normalized { x0, y0, x1, y1 }) => {
+      write_u32(out, 2)?;
+      for value in [x0, y0, x1, y1] {
+        write_f64(out, value)?;
+      }
+    }
+  }
+  write_u32(out, camera.crop_full_frame as u32)?;
+  match camera.time_budget {
+    None => write_u32(out, 0)?,
+    Some(budget) => {
+      write_u32(out, 1)?;
+      write_f64(out, budget.as_secs_f64())?;
+    }
+  }
+  match camera.adaptive {
+    None => write_u32(out, 0)?,
+    Some(adaptive) => {
+      write_u32(out, 1)?;
+      write_u32(out, adaptive.min_samples)?;
+      write_u32(out, adaptive.max_samples)?;
+      write_f64(out, adaptive.threshold)?;
+    }
+  }
+  let sampler = SamplerKind::ALL.iter().position(|&kind| kind == camera.sampler).unwrap();
+  write_u32(out, sampler as u32)?;
+  let filter = FilterKind::ALL.iter().position(|&kind| kind == camera.filter.kind).unwrap();
+  write_u32(out, filter as u32)?;
+  write_f64(out, camera.filter.radius)?;
+  match camera.stereo {
+    None => write_u32(out, 0)?,
+    Some(stereo) => {
+      write_u32(out, 1)?;
+      write_f64(out, stereo.interocular)?;
+      write_u32(out, stereo.convergence as u32)?;
+      write_u32(out, stereo.layout as u32)?;
+    }
+  }
+  write_u32(out, camera.transparent_background as u32)?;
+  write_u32(out, camera.aovs.len() as u32)?;
+  for aov in &camera.aovs {
+    write_u32(out, Aov::ALL.iter().position(|kind| kind == aov).unwrap() as u32)?;
+  }
+
+  write_u32(out, film.width as u32)?;
+  write_u32(out, film.height as u32)?;
+  write_u64(out, film.seed)?;
+  for sum in &film.pixels {
+    write_vec3(out, sum.radiance)?;
+    write_f64(out, sum.luminance_sq)?;
+    write_u32(out, sum.covered)?;
+    write_u32(out, sum.samples)?;
+  }
+  write_u32(out, !film.splats.is_empty() as u32)?;
+  for splat in &film.splats {
+    for value in splat.weighted {
+      write_i128(out, value)?;
+    }
+    write_i128(out, splat.covered)?;
+    write_i128(out, splat.weight)?;
+  }
+  Ok(())
+}
+
+pub fn read_checkpoint<R: Read>(input: &mut R) -> io::Result<Checkpoint> {
+  let mut magic = [0; 8];
+  input.read_exact(&mut magic)?;
+  if &magic != MAGIC {
+    return Err(invalid("not a render checkpoint"));
+  }
+  let version = read_u32(input)?;
+  if version != VERSION {
+    return Err(invalid(&format!("unsupported checkpoint version {}", version)));
+  }
+
+  let scene_len = read_u32(input)? as usize;
+  let mut scene = vec![0; scene_len];
+  input.read_exact(&mut scene)?;
+  let scene = String::from_utf8(scene).map_err(|_| invalid("scene name is not UTF-8"))?;
+  let layout_seed = read_u64(input)?;
+
+  let camera = CameraSettings {
+    aspect_ratio: read_f64(input)?,
+    image_width: read_u32(input)? as i32,
+    samples_per_pixel: read_u32(input)? as i32,
+    max_depth: read_u32(input)? as i32,
+    vfov: read_f64(input)?,
+    projection: read_projection(input)?,
+    lookfrom: read_vec3(input)?,
+    lookat: read_vec3(input)?,
+    vup: read_vec3(input)?,
+    defocus_angle: read_f64(input)?,
+    focus_dist: read_f64(input)?,
+    shutter_open: read_f64(input)?,
+    shutter_close: read_f64(input)?,
+    pass_samples: read_u32(input)? as i32,
+    crop: match read_u32(input)? {
+      0 => None,
+      1 => Some(CropWindow::Pixels {
+        x0: read_u64(input)? as usize,
+        y0: read_u64(input)? as usize,
+        x1: read_u64(input)? as usize,
+        y1: read_u64(input)? as usize,
+      }),
+      2 => Some(CropWindow::Normalized {
+        x0: read_f64(input)?,
+        y0: read_f64(input)?,
+        x1: read_f64(input)?,
+        y1: read_f64(input)?,
+      }),
+      _ => return Err(invalid("unknown crop window kind")),
+    },
+    crop_full_frame: read_u32(input)? != 0,
+    time_budget: match read_u32(input)? {
+      0 => None,
+      _ => Some(Duration::try_from_secs_f64(read_f64(input)?).map_err(|_| invalid("invalid time budget"))?),
+    },
+    adaptive: match read_u32(input)? {
+      0 => None,
+      _ => Some(AdaptiveSampling {
+        min_samples: read_u32(input)?,
+        max_samples: read_u32(input)?,
+        threshold: read_f64(input)?,
+      }),
+    },
+    sampler: *SamplerKind::ALL
+      .get(read_u32(input)? as usize)
+      .ok_or_else(|| invalid("unknown sampler"))?,
+    filter: Filter {
+      kind: *FilterKind::ALL
+        .get(read_u32(input)? as usize)
+        .ok_or_else(|| invalid("unknown reconstruction filter"))?,
+      radius: read_f64(input)?,
+    },
+    stereo: match read_u32(input)? {
+      0 => None,
+      _ => Some(Stereo {
+        interocular: read_f64(input)?,
+        convergence: match read_u32(input)? {
+          0 => Convergence::OffAxis,
+          1 => Convergence::ToeIn,
+          _ => return Err(invalid("unknown stereo convergence")),
+        },
+        layout: match read_u32(input)? {
+          0 => StereoLayout::SideBySide,
+          1 => StereoLayout::TopBottom,
+          _ => return Err(invalid("unknown stereo layout")),
+        },
+      }),
+    },
+    transparent_background: read_u32(input)? != 0,
+    aovs: (0..read_u32(input)?)
+      .map(|_| Aov::ALL.get(read_u32(input)? as usize).copied().ok_or_else(|| invalid("unknown AOV")))
+      .collect::<io::Result<_>>()?,
+  };
+
+  let width = read_u32(input)? as usize;
+  let height = read_u32(input)? as usize;
+  // Checked before anything is allocated, so a damaged file fails here
+  // rather than when the render starts.
+  let mut expected = Camera::new();
+  camera.apply(&mut expected);
+  if (width, height) != expected.film_size() {
+    return Err(invalid(&format!(
+      "film is {}x{} but the camera settings give {}x{}",
+      width,
+      height,
+      expected.film_size().0,
+      expected.film_size().1
+    )));
+  }
+  if expected.crop_bounds().is_none() {
+    return Err(invalid("crop window lies outside the image"));
+  }
+  let mut film = Accumulator::new(width, height, read_u64(input)?);
+  for sum in film.pixels.iter_mut() {
+    *sum = PixelSum {
+      radiance: read_vec3(input)?,
+      luminance_sq: read_f64(input)?,
+      covered: read_u32(input)?,
+      samples: read_u32(input)?,
+    };
+  }
+  if read_u32(input)? != 0 {
+    film.splats = (0..width * height)
+      .map(|_| {
+        Ok(Splat {
+          weighted: [read_i128(input)?, read_i128(input)?, read_i128(input)?],
+          covered: read_i128(input)?,
+          weight: read_i128(input)?,
+        })
+      })
+      .collect::<io::Result<_>>()?;
+  }
+
+  Ok(Checkpoint { scene, layout_seed, camera, film })
+}
+
+fn write_projection<W: Write>(out: &mut W, projection: &Projection) -> io::Result<()> {
+  match *projection {
+    Projection::Perspective => write_u32(out, 0),
+    Projection::Orthographic { view_width } => {
+      write_u32(out, 1)?;
+      write_f64(out, view_width)
+    }
+    Projection::Equirectangular => write_u32(out, 2),
+    Projection::CubeMap => write_u32(out, 3),
+    Projection::Fisheye { mapping, fov } => {
+      write_u32(out, 4)?;
+      write_u32(out, mapping as u32)?;
+      write_f64(out, fov)
+    }
+  }
+}
+
+fn read_projection<R: Read>(input: &mut R) -> io::Result<Projection> {
+  match read_u32(input)? {
+    0 => Ok(Projection::Perspective),
+    1 => Ok(Projection::Orthographic { view_width: read_f64(input)? }),
+    2 => Ok(Projection::Equirectangular),
+    3 => Ok(Projection::CubeMap),
+    4 => {
+      let mapping = match read_u32(input)? {
+        0 => FisheyeMapping::Equidistant,
+        1 => FisheyeMapping::Equisolid,
+        _ => return Err(invalid("unknown fisheye mapping")),
+      };
+      Ok(Projection::Fisheye { mapping, fov: read_f64(input)? })
+    }
+    _ => Err(invalid("unknown camera projection")),
+  }
+}
+
+fn invalid(message: &str) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn write_u32<W: Write>(out: &mut W, value: u32) -> io::Result<()> {
+  out.write_all(&value.to_le_bytes())
+}
+
+fn write_u64<W: Write>(out: &mut W, value: u64) -> io::Result<()> {
+  out.write_all(&value.to_le_bytes())
+}
+
+fn write_f64<W: Write>(out: &mut W, value: f64) -> io::Result<()> {
+  out.write_all(&value.to_le_bytes())
+}
+
+fn write_i128<W: Write>(out: &mut W, value: i128) -> io::Result<()> {
+  out.write_all(&value.to_le_bytes())
+}
+
+fn write_vec3<W: Write>(out: &mut W, v: Vec3) -> io::Result<()> {
+  for axis in 0..3 {
+    write_f64(out, v[axis])?;
+  }
+  Ok(())
+}
+
+fn read_u32<R: Read>(input: &mut R) -> io::Result<u32> {
+  let mut bytes = [0; 4];
+  input.read_exact(&mut bytes)?;
+  Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(input: &mut R) -> io::Result<u64> {
+  let mut bytes = [0; 8];
+  input.read_exact(&mut bytes)?;
+  Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_i128<R: Read>(input: &mut R) -> io::Result<i128> {
+  let mut bytes = [0; 16];
+  input.read_exact(&mut bytes)?;
+  Ok(i128::from_le_bytes(bytes))
+}
+
+fn read_f64<R: Read>(input: &mut R) -> io::Result<f64> {
+  Ok(f64::from_bits(read_u64(input)?))
+}
+
+fn read_vec3<R: Read>(input: &mut R) -> io::Result<Vec3> {
+  Ok(Vec3::from_values(read_f64(input)?, read_f64(input)?, read_f64(input)?))
+}
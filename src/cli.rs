@@ -0,0 +1,464 @@
+use rustytracer::animation::Interpolation;
+use rustytracer::aov::Aov;
+use rustytracer::camera::{AdaptiveSampling, CameraOverrides, CropWindow};
+use rustytracer::color::{Dither, TransferFunction};
+use rustytracer::image_writer::ImageFormat;
+use rustytracer::scenes::SceneOptions;
+use rustytracer::filter::{Filter, FilterKind};
+use rustytracer::projection::{FisheyeMapping, Projection};
+use rustytracer::sampler::SamplerKind;
+use rustytracer::stereo::{Convergence, Stereo, StereoLayout};
+use rustytracer::tiles::TileOrder;
+use rustytracer::tonemap::ToneMap;
+use rustytracer::vec3::Vec3;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub const USAGE: &str = "\
+Usage:
+  raytracer                          Start the interactive scene menu
+  raytracer list-scenes              List the available preset scenes
+  raytracer render --scene <name> [options]
+  raytracer render --resume <checkpoint> [options]
+  raytracer render --scene <name> --frames <first-last> [--orbit <deg>]
+                   [--keyframes <path>] [options]
+  raytracer help                     Show this message
+
+Render options:
+  -s, --scene <name>         Preset scene to render (see list-scenes)
+  -o, --output <path>        Output file (defaults to the scene's own name)
+  -f, --format <fmt>         Image format: ppm, ppm16, ppm-ascii, png, png16,
+                             png-rgba, png16-rgba, pfm, hdr, exr
+                             (default: from the output extension)
+      --seed <n>             Seed for the scene layout and the sample generator
+      --aov <list>           Extra passes: depth, normal, position, albedo,
+                             material-id, object-id, sample-count or all
+                             (comma-separated); stored as EXR layers or as
+                             <output>.<pass>.exr
+  -w, --width <px>           Image width in pixels
+      --aspect-ratio <r>     Aspect ratio as a number or W:H (e.g. 16:9)
+      --spp <n>              Samples per pixel
+      --depth <n>            Maximum ray bounce depth
+      --vfov <deg>           Vertical field of view in degrees
+      --projection <kind>    perspective (default), orthographic,
+                             equirectangular (2:1), cubemap (3:2), fisheye or
+                             fisheye-equisolid (1:1); panoramic projections
+                             set the aspect ratio themselves
+      --view-width <w>       Width of an orthographic view in world units
+                             (implies --projection orthographic)
+      --fisheye-fov <deg>    Fisheye field of view, up to 360 (default 180;
+                             implies --projection fisheye)
+      --stereo <layout>      Render both eyes: side-by-side or top-bottom; with
+                             equirectangular gives omni-directional stereo
+      --interocular <d>      Eye separation in world units (default: a 30th
+                             of the focus distance)
+      --convergence <kind>   off-axis (default) or toe-in
+      --lookfrom <x,y,z>     Camera position
+      --lookat <x,y,z>       Point the camera looks at
+      --vup <x,y,z>          Camera up vector
+      --defocus-angle <deg>  Aperture cone angle (0 disables depth of field)
+      --focus-dist <d>       Distance to the plane of perfect focus
+      --shutter <open,close> Shutter interval for motion blur (default 0,0:
+                             no blur; the motion preset uses 0,1)
+      --sampler <kind>       independent (default), stratified, halton, sobol,
+                             blue-noise
+      --filter <kind>        Pixel reconstruction filter: box (default), tent,
+                             gaussian, mitchell, lanczos
+      --filter-radius <px>   Filter reach in pixels (default: 0.5 box, 1 tent,
+                             1.5 gaussian, 2 mitchell and lanczos)
+      --tile-size <px>       Edge length of render tiles (default 32)
+      --tile-order <order>   spiral (default), scanline, hilbert, morton
+      --crop <x0,y0,x1,y1>   Only render this pixel region (ends exclusive)
+      --crop-normalized <x0,y0,x1,y1>
+                             Crop window as fractions of the image size
+      --crop-full-frame      Write the full-size frame with only the crop filled
+      --time-budget <s>      Keep adding samples for s seconds instead of a fixed
+                             count (--spp becomes a cap); writes sample counts
+      --adaptive <err>       Stop sampling pixels whose relative error (95%
+                             confidence) is below err, e.g. 0.02, and spend the
+                             savings on noisy pixels
+      --min-spp <n>          Samples before a pixel may stop (adaptive, default 16)
+      --max-spp <n>          Per-pixel cap (adaptive, default 4x --spp)
+      --heatmap <path>       Also write a PNG heatmap of per-pixel sample counts
+      --progressive <n>      Render in passes of n samples per pixel, saving a
+                             snapshot of the output after each pass
+      --snapshot-interval <s>
+                             Write progressive snapshots at most every s seconds
+      --checkpoint <path>    Save the accumulated samples to <path> after each
+                             pass (16 spp unless --progressive is given)
+      --checkpoint-interval <s>
+                             Save checkpoints at most every s seconds
+      --resume <path>        Continue a checkpointed render up to --spp (or the
+                             original count), still checkpointing to <path>;
+                             --scene may be omitted
+
+Animation options:
+      --frames <first-last>  Render this frame range, numbering the outputs: a
+                             run of # in the output name becomes the frame
+                             number, otherwise _0001 style suffixes are added
+      --orbit <deg>          Turn the camera this far around --lookat over the
+                             frame range (360 makes a looping turntable)
+      --keyframes <path>     Camera keyframes, one per line: a frame number and
+                             any of lookfrom=x,y,z lookat=x,y,z vfov=deg
+                             focus-dist=d defocus-angle=deg
+      --interpolation <kind> catmull-rom (default) or linear
+
+Display options (PPM and PNG only; PFM, HDR and EXR stay linear):
+      --exposure <ev>        Exposure adjustment in stops
+      --tonemap <curve>      clamp, reinhard, reinhard-extended, hable, aces, agx
+      --white <l>            White point for reinhard-extended (default 4)
+      --transfer <curve>     Output encoding: srgb (default), rec709, gamma2.2, gamma2
+      --dither <kind>        none (default), triangular, blue-noise";
+
+#[derive(Debug, Clone)]
+pub enum Command {
+    Interactive,
+    ListScenes,
+    Render(Box<RenderArgs>),
+    Help,
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderArgs {
+    /// May be left out when resuming; the checkpoint names its scene.
+    pub scene: Option<String>,
+    pub resume: Option<PathBuf>,
+    pub options: SceneOptions,
+    /// Renders a frame range instead of a single image.
+    pub animation: Option<AnimationArgs>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimationArgs {
+    pub frames: RangeInclusive<u32>,
+    /// Degrees to orbit over the frame range.
+    pub orbit: Option<f64>,
+    pub keyframes: Option<PathBuf>,
+    pub interpolation: Interpolation,
+}
+
+pub fn parse_args(args: &[String]) -> Result<Command, String> {
+    let Some((command, rest)) = args.split_first() else {
+        return Ok(Command::Interactive);
+    };
+
+    match command.as_str() {
+        "render" => parse_render(rest).map(|args| Command::Render(Box::new(args))),
+        "list-scenes" | "list" => {
+            expect_no_args(command, rest)?;
+            Ok(Command::ListScenes)
+        }
+        "help" | "--help" | "-h" => Ok(Command::Help),
+        "interactive" => {
+            expect_no_args(command, rest)?;
+            Ok(Command::Interactive)
+        }
+        other => Err(format!("unknown command '{}'", other)),
+    }
+}
+
+fn expect_no_args(command: &str, rest: &[String]) -> Result<(), String> {
+    match rest.first() {
+        Some(arg) => Err(format!("'{}' takes no arguments, got '{}'", command, arg)),
+        None => Ok(()),
+    }
+}
+
+fn parse_render(args: &[String]) -> Result<RenderArgs, String> {
+    let mut scene = None;
+    let mut options = SceneOptions::default();
+    let mut overrides = CameraOverrides::default();
+    let mut white_point = None;
+    let mut resume = None;
+    let mut frames = None;
+    let mut orbit = None;
+    let mut keyframes = None;
+    let mut interpolation = None;
+    let mut view_width = None;
+    let mut fisheye_fov = None;
+    let mut filter_radius = None;
+    let mut interocular = None;
+    let mut convergence = None;
+    let mut min_spp = None;
+    let mut max_spp = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        // Accept both `--flag value` and `--flag=value`.
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = || -> Result<String, String> {
+            match &inline_value {
+                Some(value) => Ok(value.clone()),
+                None => iter.next().cloned().ok_or_else(|| format!("missing value for '{}'", flag)),
+            }
+        };
+
+        match flag {
+            "-s" | "--scene" => scene = Some(value()?),
+            "-o" | "--output" => options.output = Some(value()?),
+            "-f" | "--format" => options.format = Some(parse_format(&value()?)?),
+            "--aov" | "--aovs" => overrides.aovs = Some(parse_aovs(&value()?)?),
+            "--seed" => options.seed = Some(parse_number(flag, &value()?)?),
+            "-w" | "--width" => overrides.image_width = Some(parse_positive(flag, &value()?)?),
+            "--aspect-ratio" => overrides.aspect_ratio = Some(parse_aspect_ratio(&value()?)?),
+            "--spp" | "--samples" => overrides.samples_per_pixel = Some(parse_positive(flag, &value()?)?),
+            "--depth" | "--max-depth" => overrides.max_depth = Some(parse_positive(flag, &value()?)?),
+            "--vfov" => overrides.vfov = Some(parse_number(flag, &value()?)?),
+            "--projection" => overrides.projection = Some(parse_named(flag, &value()?, Projection::from_name)?),
+            "--view-width" => {
+                let width: f64 = parse_number(flag, &value()?)?;
+                if !(width > 0.0 && width.is_finite()) {
+                    return Err(format!("'{}' must be greater than zero", flag));
+                }
+                view_width = Some(width);
+            }
+            "--fisheye-fov" => {
+                let fov: f64 = parse_number(flag, &value()?)?;
+                if !(fov > 0.0 && fov <= 360.0) {
+                    return Err(format!("'{}' must be between 0 and 360 degrees", flag));
+                }
+                fisheye_fov = Some(fov);
+            }
+            "--stereo" => overrides.stereo = Some(Stereo::new(parse_named(flag, &value()?, StereoLayout::from_name)?)),
+            "--interocular" => {
+                let distance: f64 = parse_number(flag, &value()?)?;
+                if !(distance > 0.0 && distance.is_finite()) {
+                    return Err(format!("'{}' must be greater than zero", flag));
+                }
+                interocular = Some(distance);
+            }
+            "--convergence" => convergence = Some(parse_named(flag, &value()?, Convergence::from_name)?),
+            "--lookfrom" => overrides.lookfrom = Some(parse_vec3(flag, &value()?)?),
+            "--lookat" => overrides.lookat = Some(parse_vec3(flag, &value()?)?),
+            "--vup" => overrides.vup = Some(parse_vec3(flag, &value()?)?),
+            "--defocus-angle" => overrides.defocus_angle = Some(parse_number(flag, &value()?)?),
+            "--focus-dist" => overrides.focus_dist = Some(parse_number(flag, &value()?)?),
+            "--shutter" => {
+                let [open, close] = parse_list::<f64, 2>(flag, &value()?)?;
+                if !(open.is_finite() && close.is_finite() && open <= close) {
+                    return Err(format!("'{}' needs open <= close", flag));
+                }
+                overrides.shutter_open = Some(open);
+                overrides.shutter_close = Some(close);
+            }
+            "--tile-size" => overrides.tile_size = Some(parse_positive(flag, &value()?)? as usize),
+            "--sampler" => overrides.sampler = Some(parse_named(flag, &value()?, SamplerKind::from_name)?),
+            "--tile-order" => overrides.tile_order = Some(parse_named(flag, &value()?, TileOrder::from_name)?),
+            "--crop" => {
+                let [x0, y0, x1, y1] = parse_list::<usize, 4>(flag, &value()?)?;
+                if x1 <= x0 || y1 <= y0 {
+                    return Err(format!("'{}' needs x1 > x0 and y1 > y0", flag));
+                }
+                overrides.crop = Some(CropWindow::Pixels { x0, y0, x1, y1 });
+            }
+            "--crop-normalized" => {
+                let [x0, y0, x1, y1] = parse_list::<f64, 4>(flag, &value()?)?;
+                if !(0.0 <= x0 && x0 < x1 && x1 <= 1.0 && 0.0 <= y0 && y0 < y1 && y1 <= 1.0) {
+                    return Err(format!("'{}' needs 0 <= x0 < x1 <= 1 and 0 <= y0 < y1 <= 1", flag));
+                }
+                overrides.crop = Some(CropWindow::Normalized { x0, y0, x1, y1 });
+            }
+            "--crop-full-frame" => overrides.crop_full_frame = Some(true),
+            "--time-budget" => {
+                let budget = parse_seconds(flag, &value()?)?;
+                if budget.is_zero() {
+                    return Err(format!("'{}' must be greater than zero", flag));
+                }
+                overrides.time_budget = Some(budget);
+            }
+            "--adaptive" => {
+                let threshold: f64 = parse_number(flag, &value()?)?;
+                if threshold.is_nan() || threshold <= 0.0 {
+                    return Err(format!("'{}' must be greater than zero", flag));
+                }
+                overrides.adaptive.get_or_insert_with(AdaptiveSampling::new).threshold = threshold;
+            }
+            "--filter" => overrides.filter = Some(Filter::new(parse_named(flag, &value()?, FilterKind::from_name)?)),
+            "--filter-radius" => {
+                let radius: f64 = parse_number(flag, &value()?)?;
+                if !(radius > 0.0 && radius.is_finite()) {
+                    return Err(format!("'{}' must be greater than zero", flag));
+                }
+                filter_radius = Some(radius);
+            }
+            "--min-spp" => min_spp = Some(parse_positive(flag, &value()?)? as u32),
+            "--max-spp" => max_spp = Some(parse_positive(flag, &value()?)? as u32),
+            "--heatmap" => options.heatmap = Some(PathBuf::from(value()?)),
+            "--progressive" => overrides.pass_samples = Some(parse_positive(flag, &value()?)?),
+            "--snapshot-interval" => options.snapshot_interval = Some(parse_seconds(flag, &value()?)?),
+            "--checkpoint" => options.checkpoint = Some(PathBuf::from(value()?)),
+            "--checkpoint-interval" => options.checkpoint_interval = Some(parse_seconds(flag, &value()?)?),
+            "--resume" => resume = Some(PathBuf::from(value()?)),
+            "--frames" => frames = Some(parse_frames(flag, &value()?)?),
+            "--orbit" => orbit = Some(parse_number::<f64>(flag, &value()?)?),
+            "--keyframes" => keyframes = Some(PathBuf::from(value()?)),
+            "--interpolation" => interpolation = Some(parse_named(flag, &value()?, Interpolation::from_name)?),
+            "--exposure" => options.display.exposure = parse_number(flag, &value()?)?,
+            "--tonemap" => options.display.tone_map = parse_tone_map(&value()?)?,
+            "--transfer" => options.display.transfer = parse_named(flag, &value()?, TransferFunction::from_name)?,
+            "--dither" => options.display.dither = parse_named(flag, &value()?, Dither::from_name)?,
+            "--white" => white_point = Some(parse_number::<f64>(flag, &value()?)?),
+            other => return Err(format!("unknown option '{}'", other)),
+        }
+    }
+
+    if let Some(white) = white_point {
+        match &mut options.display.tone_map {
+            ToneMap::ReinhardExtended { white: w } => *w = white,
+            _ => return Err("'--white' only applies to '--tonemap reinhard-extended'".to_string()),
+        }
+    }
+
+    if let Some(view_width) = view_width {
+        match overrides.projection {
+            None | Some(Projection::Orthographic { .. }) => {
+                overrides.projection = Some(Projection::Orthographic { view_width });
+            }
+            Some(_) => return Err("'--view-width' only applies to the orthographic projection".to_string()),
+        }
+    }
+    if let Some(fov) = fisheye_fov {
+        match &mut overrides.projection {
+            None => overrides.projection = Some(Projection::Fisheye { mapping: FisheyeMapping::Equidistant, fov }),
+            Some(Projection::Fisheye { fov: current, .. }) => *current = fov,
+            Some(_) => return Err("'--fisheye-fov' only applies to fisheye projections".to_string()),
+        }
+    }
+    if interocular.is_some() || convergence.is_some() {
+        let Some(stereo) = &mut overrides.stereo else {
+            return Err("'--interocular' and '--convergence' only apply with '--stereo'".to_string());
+        };
+        stereo.interocular = interocular.unwrap_or(stereo.interocular);
+        stereo.convergence = convergence.unwrap_or(stereo.convergence);
+    }
+    if let Some(radius) = filter_radius {
+        overrides.filter.get_or_insert_with(Filter::default).radius = radius;
+    }
+    if min_spp.is_some() || max_spp.is_some() {
+        let Some(adaptive) = &mut overrides.adaptive else {
+            return Err("'--min-spp' and '--max-spp' only apply with '--adaptive'".to_string());
+        };
+        adaptive.min_samples = min_spp.unwrap_or(adaptive.min_samples);
+        adaptive.max_samples = max_spp.unwrap_or(adaptive.max_samples);
+    }
+
+    if interpolation.is_some() && keyframes.is_none() {
+        return Err("'--interpolation' only applies with '--keyframes'".to_string());
+    }
+    let animation = match frames {
+        Some(frames) => {
+            if resume.is_some() || options.checkpoint.is_some() {
+                return Err("'--frames' cannot be combined with checkpoints".to_string());
+            }
+            Some(AnimationArgs { frames, orbit, keyframes, interpolation: interpolation.unwrap_or_default() })
+        }
+        None if orbit.is_some() || keyframes.is_some() => {
+            return Err("'--orbit' and '--keyframes' need a '--frames' range".to_string());
+        }
+        None => None,
+    };
+
+    options.camera = overrides;
+    if scene.is_none() && resume.is_none() {
+        return Err("render requires --scene <name>".to_string());
+    }
+    Ok(RenderArgs { scene, resume, options, animation })
+}
+
+/// A frame range as `first-last` (inclusive), or a single frame.
+fn parse_frames(flag: &str, value: &str) -> Result<RangeInclusive<u32>, String> {
+    let (first, last) = match value.split_once('-') {
+        Some((first, last)) => (parse_number(flag, first)?, parse_number(flag, last)?),
+        None => {
+            let frame = parse_number(flag, value)?;
+            (frame, frame)
+        }
+    };
+    if last < first {
+        return Err(format!("'{}' needs first <= last", flag));
+    }
+    Ok(first..=last)
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid value '{}' for '{}'", value, flag))
+}
+
+fn parse_positive(flag: &str, value: &str) -> Result<i32, String> {
+    match parse_number::<i32>(flag, value)? {
+        n if n > 0 => Ok(n),
+        _ => Err(format!("'{}' must be greater than zero", flag)),
+    }
+}
+
+fn parse_seconds(flag: &str, value: &str) -> Result<Duration, String> {
+    let seconds: f64 = parse_number(flag, value)?;
+    if !(seconds >= 0.0 && seconds.is_finite()) {
+        return Err(format!("'{}' must be a non-negative number of seconds", flag));
+    }
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+fn parse_format(value: &str) -> Result<ImageFormat, String> {
+    ImageFormat::from_name(value).ok_or_else(|| format!("unknown image format '{}'", value))
+}
+
+fn parse_named<T>(flag: &str, value: &str, from_name: fn(&str) -> Option<T>) -> Result<T, String> {
+    from_name(value).ok_or_else(|| format!("invalid value '{}' for '{}'", value, flag))
+}
+
+fn parse_aovs(value: &str) -> Result<Vec<Aov>, String> {
+    if value.eq_ignore_ascii_case("all") {
+        return Ok(Aov::ALL.to_vec());
+    }
+    let mut aovs = Vec::new();
+    for name in value.split(',') {
+        let aov = Aov::from_name(name.trim()).ok_or_else(|| format!("unknown AOV '{}'", name))?;
+        if !aovs.contains(&aov) {
+            aovs.push(aov);
+        }
+    }
+    Ok(aovs)
+}
+
+fn parse_tone_map(value: &str) -> Result<ToneMap, String> {
+    ToneMap::from_name(value).ok_or_else(|| format!("unknown tone curve '{}'", value))
+}
+
+fn parse_aspect_ratio(value: &str) -> Result<f64, String> {
+    let ratio = match value.split_once(':') {
+        Some((w, h)) => {
+            parse_number::<f64>("--aspect-ratio", w)? / parse_number::<f64>("--aspect-ratio", h)?
+        }
+        None => parse_number("--aspect-ratio", value)?,
+    };
+    if ratio.is_finite() && ratio > 0.0 {
+        Ok(ratio)
+    } else {
+        Err(format!("invalid aspect ratio '{}'", value))
+    }
+}
+
+fn parse_vec3(flag: &str, value: &str) -> Result<Vec3, String> {
+    let [x, y, z] = parse_list::<f64, 3>(flag, value)?;
+    Ok(Vec3::from_values(x, y, z))
+}
+
+fn parse_list<T: std::str::FromStr + Copy + Default, const N: usize>(flag: &str, value: &str) -> Result<[T; N], String> {
+    let parts: Vec<&str> = value.split(',').collect();
+    if parts.len() != N {
+        return Err(format!("'{}' expects {} comma-separated numbers, got '{}'", flag, N, value));
+    }
+    let mut values = [T::default(); N];
+    for (slot, part) in values.iter_mut().zip(parts) {
+        *slot = parse_number(flag, part)?;
+    }
+    Ok(values)
+}
@@ -1,29 +1,154 @@
-use crate::vec3::Vec3;
-use crate::interval::Interval;
-use std::io::{self, Write};
+use crate::vec3::{dot, Vec3};
 
 pub type Color = Vec3;
 
+/// Rec. 709 relative luminance of a linear colour.
+pub fn luminance(color: &Color) -> f64 {
+  dot(color, &Color::from_values(0.2126, 0.7152, 0.0722))
+}
+
 pub fn linear_to_gamma(linear_component: f64) -> f64 {
   if linear_component > 0.0 {
-    return  linear_component.sqrt();
+    return linear_component.sqrt();
   }
-  return 0.0;
+  0.0
 }
 
-pub fn write_color<W: Write>(out: &mut W, pixel_color: &Color) -> io::Result<()> {
-  let mut red = pixel_color.x();
-  let mut green = pixel_color.y();
-  let mut blue = pixel_color.z();
+/// Opto-electronic transfer function used to encode display-linear values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TransferFunction {
+  /// Piecewise sRGB curve from IEC 61966-2-1.
+  #[default]
+  Srgb,
+  /// ITU-R BT.709 camera OETF.
+  Rec709,
+  /// Pure power law with exponent 1/2.2.
+  Gamma22,
+  /// Square root, the tracer's original approximation.
+  Gamma2,
+}
 
-  red = linear_to_gamma(red);
-  green = linear_to_gamma(green);
-  blue = linear_to_gamma(blue);
+impl TransferFunction {
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name.to_ascii_lowercase().as_str() {
+      "srgb" => Some(TransferFunction::Srgb),
+      "rec709" | "bt709" => Some(TransferFunction::Rec709),
+      "gamma2.2" | "gamma22" => Some(TransferFunction::Gamma22),
+      "gamma2" | "gamma2.0" | "sqrt" => Some(TransferFunction::Gamma2),
+      _ => None,
+    }
+  }
 
-  let intensity = Interval::from_range(0.000, 0.999);
-  let red_byte = (256.0 * intensity.clamp(red) ) as i32;
-  let green_byte = (256.0 * intensity.clamp(green)) as i32;
-  let blue_byte = (256.0 * intensity.clamp(blue)) as i32;
+  /// Encodes a display-linear value in `[0, 1]`.
+  pub fn encode(&self, linear: f64) -> f64 {
+    let linear = linear.clamp(0.0, 1.0);
+    match self {
+      TransferFunction::Srgb => {
+        if linear <= 0.0031308 {
+          12.92 * linear
+        } else {
+          1.055 * linear.powf(1.0 / 2.4) - 0.055
+        }
+      }
+      TransferFunction::Rec709 => {
+        if linear < 0.018 {
+          4.5 * linear
+        } else {
+          1.099 * linear.powf(0.45) - 0.099
+        }
+      }
+      TransferFunction::Gamma22 => linear.powf(1.0 / 2.2),
+      TransferFunction::Gamma2 => linear_to_gamma(linear),
+    }
+  }
 
-  writeln!(out,"{} {} {}", red_byte, green_byte, blue_byte)
-}
\ No newline at end of file
+  /// Exponent recorded in a PNG `gAMA` chunk for pure power curves.
+  pub fn png_gamma(&self) -> Option<f32> {
+    match self {
+      TransferFunction::Gamma22 => Some(1.0 / 2.2),
+      TransferFunction::Gamma2 => Some(0.5),
+      TransferFunction::Srgb | TransferFunction::Rec709 => None,
+    }
+  }
+}
+
+/// Noise added before quantization to break up banding in smooth gradients.
+/// Both kinds are derived from the pixel position, so output is reproducible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dither {
+  #[default]
+  None,
+  /// Triangular-PDF white noise spanning ±1 LSB.
+  Triangular,
+  /// Uniform ±0.5 LSB noise from a tiled blue-noise mask.
+  BlueNoise,
+}
+
+impl Dither {
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name.to_ascii_lowercase().as_str() {
+      "none" | "off" => Some(Dither::None),
+      "triangular" | "tpdf" => Some(Dither::Triangular),
+      "blue-noise" | "blue" => Some(Dither::BlueNoise),
+      _ => None,
+    }
+  }
+
+  /// Offset in units of one quantization step for channel `channel` of pixel (x, y).
+  pub fn offset(&self, x: usize, y: usize, channel: usize) -> f64 {
+    match self {
+      Dither::None => 0.0,
+      Dither::Triangular => {
+        let key = ((y as u64) << 32) ^ ((x as u64) << 2) ^ channel as u64;
+        let h = crate::rtweekend::hash_u64(key);
+        let u1 = (h >> 40) as f64 / (1u64 << 24) as f64;
+        let u2 = ((h >> 16) & 0xff_ffff) as f64 / (1u64 << 24) as f64;
+        u1 + u2 - 1.0
+      }
+      Dither::BlueNoise => {
+        // Shift the tile per channel so the three channels are decorrelated.
+        crate::blue_noise::sample(x + 19 * channel, y + 37 * channel) - 0.5
+      }
+    }
+  }
+}
+
+/// Rounds an encoded value in `[0, 1]` to the nearest of `0..=max_value`,
+/// after adding `dither` steps of noise.
+pub fn quantize(encoded: f64, max_value: u32, dither: f64) -> u32 {
+  let max = max_value as f64;
+  (encoded * max + dither).round().clamp(0.0, max) as u32
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// 8-bit codes from the IEC 61966-2-1 and BT.709 formulas evaluated
+  /// independently, covering both sides of each breakpoint.
+  const REFERENCE: [(f64, u32, u32); 14] = [
+    // (linear, sRGB, Rec. 709)
+    (0.0, 0, 0),
+    (0.001, 3, 1),
+    (0.0031308, 10, 4),
+    (0.0031309, 10, 4),
+    (0.01, 25, 11),
+    (0.018, 36, 21),
+    (0.0181, 37, 21),
+    (0.05, 63, 48),
+    (0.18, 118, 104),
+    (0.214, 127, 115),
+    (0.5, 188, 180),
+    (0.75, 225, 221),
+    (0.9, 243, 242),
+    (1.0, 255, 255),
+  ];
+
+  #[test]
+  fn eight_bit_codes_match_reference() {
+    for (linear, srgb, rec709) in REFERENCE {
+      assert_eq!(quantize(TransferFunction::Srgb.encode(linear), 255, 0.0), srgb, "sRGB at {}", linear);
+      assert_eq!(quantize(TransferFunction::Rec709.encode(linear), 255, 0.0), rec709, "Rec. 709 at {}", linear);
+    }
+  }
+}
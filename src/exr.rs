@@ -0,0 +1,136 @@
+use std::io::{self, Write};
+
+/// One named channel of 32-bit float samples, row-major and `width * height`
+/// long. Names follow the OpenEXR layer convention, e.g. `R` or `normal.X`.
+pub struct ExrChannel<'a> {
+  pub name: String,
+  pub data: &'a [f32],
+}
+
+/// Writes an uncompressed, single-part scanline OpenEXR file. Channels are
+/// stored as FLOAT and sorted by name, which the format requires.
+pub fn write_exr<W: Write>(out: &mut W, width: usize, height: usize, channels: &mut [ExrChannel]) -> io::Result<()> {
+  channels.sort_by(|a, b| a.name.cmp(&b.name));
+  for channel in channels.iter() {
+    if channel.data.len() != width * height {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("EXR channel '{}' has {} samples, expected {}", channel.name, channel.data.len(), width * height),
+      ));
+    }
+  }
+
+  let mut header = Vec::new();
+  header.extend_from_slice(&[0x76, 0x2f, 0x31, 0x01]);
+  header.extend_from_slice(&2u32.to_le_bytes());
+
+  let mut chlist = Vec::new();
+  for channel in channels.iter() {
+    chlist.extend_from_slice(channel.name.as_bytes());
+    chlist.push(0);
+    chlist.extend_from_slice(&2i32.to_le_bytes()); // pixel type FLOAT
+    chlist.extend_from_slice(&[0, 0, 0, 0]); // pLinear + reserved
+    chlist.extend_from_slice(&1i32.to_le_bytes()); // x sampling
+    chlist.extend_from_slice(&1i32.to_le_bytes()); // y sampling
+  }
+  chlist.push(0);
+  write_attribute(&mut header, "channels", "chlist", &chlist);
+
+  write_attribute(&mut header, "compression", "compression", &[0]);
+
+  let mut window = Vec::new();
+  for value in [0, 0, width as i32 - 1, height as i32 - 1] {
+    window.extend_from_slice(&value.to_le_bytes());
+  }
+  write_attribute(&mut header, "dataWindow", "box2i", &window);
+  write_attribute(&mut header, "displayWindow", "box2i", &window);
+  write_attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+  write_attribute(&mut header, "pixelAspectRatio", "float", &1.0f32.to_le_bytes());
+  write_attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+  write_attribute(&mut header, "screenWindowWidth", "float", &1.0f32.to_le_bytes());
+  header.push(0);
+  out.write_all(&header)?;
+
+  // Uncompressed files hold one scanline per chunk: y, byte count, then each
+  // channel's samples for that line.
+  let line_bytes = width * 4 * channels.len();
+  let chunk_bytes = 8 + line_bytes;
+  let table_end = header.len() + 8 * height;
+  for y in 0..height {
+    out.write_all(&((table_end + y * chunk_bytes) as u64).to_le_bytes())?;
+  }
+
+  let mut line = Vec::with_capacity(line_bytes);
+  for y in 0..height {
+    line.clear();
+    for channel in channels.iter() {
+      for value in &channel.data[y * width..(y + 1) * width] {
+        line.extend_from_slice(&value.to_le_bytes());
+      }
+    }
+    out.write_all(&(y as i32).to_le_bytes())?;
+    out.write_all(&(line_bytes as i32).to_le_bytes())?;
+    out.write_all(&line)?;
+  }
+
+  Ok(())
+}
+
+fn write_attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+  header.extend_from_slice(name.as_bytes());
+  header.push(0);
+  header.extend_from_slice(kind.as_bytes());
+  header.push(0);
+  header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+  header.extend_from_slice(value);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn read_u64(bytes: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap())
+  }
+
+  fn read_i32(bytes: &[u8], at: usize) -> i32 {
+    i32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+  }
+
+  #[test]
+  fn offset_table_points_at_each_scanline() {
+    let (red, green) = ([1.0f32, 2.0, 3.0, 4.0], [5.0f32, 6.0, 7.0, 8.0]);
+    // Given out of order; the file stores them sorted by name.
+    let mut channels =
+      [ExrChannel { name: "G".to_string(), data: &green }, ExrChannel { name: "R".to_string(), data: &red }];
+    let mut bytes = Vec::new();
+    write_exr(&mut bytes, 2, 2, &mut channels).unwrap();
+
+    assert_eq!(&bytes[..8], &[0x76, 0x2f, 0x31, 0x01, 2, 0, 0, 0]);
+    // Two chunks of y, byte count and two channels of two floats.
+    let line_bytes = 2 * 2 * 4;
+    let table = bytes.len() - 8 * 2 - 2 * (8 + line_bytes);
+    assert_eq!(bytes[table - 1], 0, "header must end with an empty attribute name");
+
+    for y in 0..2 {
+      let offset = read_u64(&bytes, table + 8 * y) as usize;
+      assert_eq!(offset, table + 16 + y * (8 + line_bytes));
+      assert_eq!(read_i32(&bytes, offset), y as i32);
+      assert_eq!(read_i32(&bytes, offset + 4), line_bytes as i32);
+      let samples: Vec<f32> = bytes[offset + 8..offset + 8 + line_bytes]
+        .chunks(4)
+        .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect();
+      let (g, r) = (&green[2 * y..2 * y + 2], &red[2 * y..2 * y + 2]);
+      assert_eq!(samples, [g, r].concat());
+    }
+  }
+
+  #[test]
+  fn mismatched_channel_length_is_rejected() {
+    let data = [0.0f32; 3];
+    let mut channels = [ExrChannel { name: "Y".to_string(), data: &data }];
+    let error = write_exr(&mut Vec::new(), 2, 2, &mut channels).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+  }
+}
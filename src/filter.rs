@@ -0,0 +1,234 @@
+use std::f64::consts::PI;
+
+use crate::color::Color;
+use crate::tiles::Tile;
+
+/// Shape of the pixel reconstruction filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FilterKind {
+  /// Every sample counts equally within the radius.
+  #[default]
+  Box,
+  /// Weight falls off linearly to zero at the radius.
+  Tent,
+  /// Gaussian with a standard deviation of a third of the radius, shifted
+  /// to reach zero at the radius.
+  Gaussian,
+  /// Mitchell–Netravali cubic with B = C = 1/3.
+  Mitchell,
+  /// Sinc windowed by a wider sinc, with as many lobes as the radius.
+  Lanczos,
+}
+
+impl FilterKind {
+  pub const ALL: [FilterKind; 5] =
+    [FilterKind::Box, FilterKind::Tent, FilterKind::Gaussian, FilterKind::Mitchell, FilterKind::Lanczos];
+
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name.to_ascii_lowercase().as_str() {
+      "box" => Some(FilterKind::Box),
+      "tent" | "triangle" => Some(FilterKind::Tent),
+      "gaussian" | "gauss" => Some(FilterKind::Gaussian),
+      "mitchell" | "mitchell-netravali" => Some(FilterKind::Mitchell),
+      "lanczos" | "sinc" => Some(FilterKind::Lanczos),
+      _ => None,
+    }
+  }
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      FilterKind::Box => "box",
+      FilterKind::Tent => "tent",
+      FilterKind::Gaussian => "gaussian",
+      FilterKind::Mitchell => "mitchell",
+      FilterKind::Lanczos => "lanczos",
+    }
+  }
+
+  /// Radius in pixels used when none is given.
+  pub fn default_radius(&self) -> f64 {
+    match self {
+      FilterKind::Box => 0.5,
+      FilterKind::Tent => 1.0,
+      FilterKind::Gaussian => 1.5,
+      FilterKind::Mitchell | FilterKind::Lanczos => 2.0,
+    }
+  }
+}
+
+/// Reconstruction filter: how much a sample contributes to the pixels
+/// around it, by its offset from their centres.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Filter {
+  pub kind: FilterKind,
+  /// Reach in pixels along each axis.
+  pub radius: f64,
+}
+
+impl Filter {
+  pub fn new(kind: FilterKind) -> Self {
+    Filter { kind, radius: kind.default_radius() }
+  }
+
+  /// Whether samples are spread over neighbouring pixels. The default box
+  /// filter keeps each sample in its own pixel and needs no splatting.
+  pub fn splats(&self) -> bool {
+    !(self.kind == FilterKind::Box && self.radius <= 0.5)
+  }
+
+  /// How many pixels beyond its own a sample can reach, along each axis.
+  pub fn margin(&self) -> usize {
+    (self.radius - 0.5).ceil().max(0.0) as usize
+  }
+
+  /// Weight of a sample `dx`, `dy` pixels away from a pixel centre.
+  pub fn evaluate(&self, dx: f64, dy: f64) -> f64 {
+    self.evaluate_1d(dx) * self.evaluate_1d(dy)
+  }
+
+  fn evaluate_1d(&self, x: f64) -> f64 {
+    let x = x.abs();
+    if x > self.radius {
+      return 0.0;
+    }
+    match self.kind {
+      FilterKind::Box => 1.0,
+      FilterKind::Tent => 1.0 - x / self.radius,
+      FilterKind::Gaussian => {
+        let sigma = self.radius / 3.0;
+        let gaussian = |x: f64| (-x * x / (2.0 * sigma * sigma)).exp();
+        (gaussian(x) - gaussian(self.radius)).max(0.0)
+      }
+      FilterKind::Mitchell => mitchell(2.0 * x / self.radius, 1.0 / 3.0, 1.0 / 3.0),
+      FilterKind::Lanczos => sinc(x) * sinc(x / self.radius),
+    }
+  }
+}
+
+impl Default for Filter {
+  fn default() -> Self {
+    Self::new(FilterKind::Box)
+  }
+}
+
+/// Mitchell–Netravali cubic on `[0, 2]`.
+fn mitchell(x: f64, b: f64, c: f64) -> f64 {
+  let (x2, x3) = (x * x, x * x * x);
+  let value = if x < 1.0 {
+    (12.0 - 9.0 * b - 6.0 * c) * x3 + (-18.0 + 12.0 * b + 6.0 * c) * x2 + (6.0 - 2.0 * b)
+  } else if x < 2.0 {
+    (-b - 6.0 * c) * x3 + (6.0 * b + 30.0 * c) * x2 + (-12.0 * b - 48.0 * c) * x + (8.0 * b + 24.0 * c)
+  } else {
+    0.0
+  };
+  value / 6.0
+}
+
+/// Normalized sinc, sin(πx) / πx.
+fn sinc(x: f64) -> f64 {
+  if x < 1e-8 {
+    return 1.0;
+  }
+  (PI * x).sin() / (PI * x)
+}
+
+/// Filter-weighted sums for one pixel. They are kept in fixed point, where
+/// addition is exact, so the totals do not depend on how samples were
+/// grouped into tiles and passes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Splat {
+  pub weighted: [i128; 3],
+  /// Weighted coverage, resolved into alpha.
+  pub covered: i128,
+  pub weight: i128,
+}
+
+/// Fixed-point steps per unit of weighted radiance or weight.
+const SPLAT_SCALE: f64 = (1u64 << 40) as f64;
+
+impl Splat {
+  /// Sums of samples that count as box-filtered within their own pixel.
+  pub fn from_sums(radiance: Color, covered: f64, weight: f64) -> Self {
+    Splat {
+      weighted: [0, 1, 2].map(|channel| to_fixed(radiance[channel])),
+      covered: to_fixed(covered),
+      weight: to_fixed(weight),
+    }
+  }
+
+  pub fn add_sample(&mut self, weight: f64, color: Color, hit: bool) {
+    for channel in 0..3 {
+      self.weighted[channel] += to_fixed(weight * color[channel]);
+    }
+    self.covered += to_fixed(weight * hit as u32 as f64);
+    self.weight += to_fixed(weight);
+  }
+
+  pub fn merge(&mut self, other: &Splat) {
+    for channel in 0..3 {
+      self.weighted[channel] += other.weighted[channel];
+    }
+    self.covered += other.covered;
+    self.weight += other.weight;
+  }
+
+  /// Weighted radiance, weighted coverage and total weight.
+  pub fn sums(&self) -> (Color, f64, f64) {
+    let [r, g, b] = self.weighted.map(from_fixed);
+    (Color::from_values(r, g, b), from_fixed(self.covered), from_fixed(self.weight))
+  }
+}
+
+fn to_fixed(value: f64) -> i128 {
+  (value * SPLAT_SCALE).round() as i128
+}
+
+fn from_fixed(value: i128) -> f64 {
+  value as f64 / SPLAT_SCALE
+}
+
+/// Splats from the samples of one tile, covering the tile plus the margin
+/// its filter reaches into (clipped to the image, or to one eye of a
+/// stereo pair). Workers fill these on
+/// their own and the film adds them up afterwards; the sums are exact, so
+/// the order does not matter.
+#[derive(Debug, Clone)]
+pub struct SplatTile {
+  pub x0: usize,
+  pub y0: usize,
+  pub x1: usize,
+  pub y1: usize,
+  pub splats: Vec<Splat>,
+}
+
+impl SplatTile {
+  /// Splats for `tile`, kept within `bounds` as `(x0, y0, x1, y1)`.
+  pub fn new(tile: &Tile, filter: &Filter, bounds: (usize, usize, usize, usize)) -> Self {
+    let margin = filter.margin();
+    let x0 = tile.x0.saturating_sub(margin).max(bounds.0);
+    let y0 = tile.y0.saturating_sub(margin).max(bounds.1);
+    let x1 = (tile.x1 + margin).min(bounds.2);
+    let y1 = (tile.y1 + margin).min(bounds.3);
+    SplatTile { x0, y0, x1, y1, splats: vec![Splat::default(); (x1 - x0) * (y1 - y0)] }
+  }
+
+  /// Adds a sample taken at image position (x, y), in pixel units with
+  /// pixel centres on integers, to every pixel the filter reaches.
+  pub fn add(&mut self, filter: &Filter, x: f64, y: f64, color: Color, hit: bool) {
+    let reach = |centre: f64, low: usize, high: usize| {
+      let from = (centre - filter.radius).ceil().max(low as f64) as usize;
+      let to = ((centre + filter.radius).floor() + 1.0).min(high as f64).max(from as f64) as usize;
+      from..to
+    };
+    let width = self.x1 - self.x0;
+    for py in reach(y, self.y0, self.y1) {
+      for px in reach(x, self.x0, self.x1) {
+        let weight = filter.evaluate(x - px as f64, y - py as f64);
+        if weight == 0.0 {
+          continue;
+        }
+        self.splats[(py - self.y0) * width + (px - self.x0)].add_sample(weight, color, hit);
+      }
+    }
+  }
+}
@@ -0,0 +1,199 @@
+use crate::aov::{Aov, AovLayer};
+use crate::color::{luminance, Color};
+use crate::filter::{Splat, SplatTile};
+
+/// In-memory result of a render: linear radiance with coverage alpha, the
+/// number of samples behind every pixel, and any recorded AOVs. All buffers
+/// are row-major, top row first.
+#[derive(Debug, Clone)]
+pub struct Framebuffer {
+  pub width: usize,
+  pub height: usize,
+  /// Linear RGB, averaged over the pixel's samples. When the camera renders
+  /// with a transparent background this is premultiplied by `alpha`.
+  pub pixels: Vec<Color>,
+  /// Fraction of camera rays that hit geometry, or 1.0 everywhere when the
+  /// background is opaque.
+  pub alpha: Vec<f64>,
+  pub sample_counts: Vec<u32>,
+  pub aovs: Vec<AovLayer>,
+}
+
+impl Framebuffer {
+  /// A black, fully transparent image with no samples.
+  pub fn new(width: usize, height: usize) -> Self {
+    let len = width * height;
+    Framebuffer {
+      width,
+      height,
+      pixels: vec![Color::new(); len],
+      alpha: vec![0.0; len],
+      sample_counts: vec![0; len],
+      aovs: Vec::new(),
+    }
+  }
+
+  pub fn index(&self, x: usize, y: usize) -> usize {
+    y * self.width + x
+  }
+
+  pub fn pixel(&self, x: usize, y: usize) -> Color {
+    self.pixels[self.index(x, y)]
+  }
+
+  pub fn aov(&self, aov: Aov) -> Option<&AovLayer> {
+    self.aovs.iter().find(|layer| layer.aov == aov)
+  }
+
+  /// Straight (un-premultiplied) colour of pixel `index`, as stored by
+  /// formats with a separate alpha channel.
+  pub fn straight_color(&self, index: usize) -> Color {
+    let alpha = self.alpha[index];
+    if alpha > 0.0 && alpha < 1.0 {
+      self.pixels[index] / alpha
+    } else {
+      self.pixels[index]
+    }
+  }
+
+  /// Copies out the region `x0..x1` by `y0..y1`, AOVs included.
+  pub fn crop(&self, x0: usize, y0: usize, x1: usize, y1: usize) -> Framebuffer {
+    let (width, height) = (x1 - x0, y1 - y0);
+    let indices: Vec<usize> = (y0..y1).flat_map(|y| (x0..x1).map(move |x| y * self.width + x)).collect();
+    Framebuffer {
+      width,
+      height,
+      pixels: indices.iter().map(|&i| self.pixels[i]).collect(),
+      alpha: indices.iter().map(|&i| self.alpha[i]).collect(),
+      sample_counts: indices.iter().map(|&i| self.sample_counts[i]).collect(),
+      aovs: self
+        .aovs
+        .iter()
+        .map(|layer| AovLayer {
+          aov: layer.aov,
+          planes: layer.planes.iter().map(|plane| indices.iter().map(|&i| plane[i]).collect()).collect(),
+        })
+        .collect(),
+    }
+  }
+
+  pub fn total_samples(&self) -> u64 {
+    self.sample_counts.iter().map(|&n| n as u64).sum()
+  }
+
+  /// Minimum, mean and maximum samples per pixel.
+  pub fn sample_stats(&self) -> (u32, f64, u32) {
+    let min = self.sample_counts.iter().copied().min().unwrap_or(0);
+    let max = self.sample_counts.iter().copied().max().unwrap_or(0);
+    let mean = self.total_samples() as f64 / self.sample_counts.len().max(1) as f64;
+    (min, mean, max)
+  }
+}
+
+/// Running per-pixel sums that passes of samples are added to.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PixelSum {
+  pub radiance: Color,
+  /// Sum of squared sample luminances, for the pixel's variance.
+  pub luminance_sq: f64,
+  /// Samples whose camera ray hit geometry.
+  pub covered: u32,
+  pub samples: u32,
+}
+
+impl PixelSum {
+  pub fn add_sample(&mut self, color: Color, hit: bool) {
+    let l = luminance(&color);
+    self.radiance += color;
+    self.luminance_sq += l * l;
+    self.covered += hit as u32;
+    self.samples += 1;
+  }
+
+  /// Half-width of the 95% confidence interval of the mean luminance,
+  /// relative to the mean. Very dark pixels are measured against a floor of
+  /// 0.01 so their noise, invisible after encoding, does not dominate.
+  pub fn relative_error(&self) -> f64 {
+    if self.samples < 2 {
+      return f64::INFINITY;
+    }
+    let n = self.samples as f64;
+    let mean = luminance(&self.radiance) / n;
+    let variance = ((self.luminance_sq - n * mean * mean) / (n - 1.0)).max(0.0);
+    1.96 * (variance / n).sqrt() / mean.max(0.01)
+  }
+}
+
+/// Sample sums for a whole image, kept while a render is in progress so
+/// further passes can be added and intermediate images resolved.
+#[derive(Debug, Clone)]
+pub struct Accumulator {
+  pub width: usize,
+  pub height: usize,
+  /// Random streams are derived from this seed, the pixel and its sample
+  /// count, so a saved accumulator carries all the RNG state needed to
+  /// continue rendering it.
+  pub seed: u64,
+  pub pixels: Vec<PixelSum>,
+  /// Filter-weighted sums, only kept when the reconstruction filter reaches
+  /// beyond the pixel; the plain sums above still drive sample counts and
+  /// adaptive sampling.
+  pub splats: Vec<Splat>,
+}
+
+impl Accumulator {
+  pub fn new(width: usize, height: usize, seed: u64) -> Self {
+    Accumulator { width, height, seed, pixels: vec![PixelSum::default(); width * height], splats: Vec::new() }
+  }
+
+  /// Starts keeping filter-weighted sums. Samples taken before count as
+  /// box-filtered within their own pixel.
+  pub fn enable_splats(&mut self) {
+    if self.splats.is_empty() {
+      self.splats = self
+        .pixels
+        .iter()
+        .map(|sum| Splat::from_sums(sum.radiance, sum.covered as f64, sum.samples as f64))
+        .collect();
+    }
+  }
+
+  pub fn add_splats(&mut self, tile: &SplatTile) {
+    let width = tile.x1 - tile.x0;
+    for y in tile.y0..tile.y1 {
+      for x in tile.x0..tile.x1 {
+        self.splats[y * self.width + x].merge(&tile.splats[(y - tile.y0) * width + (x - tile.x0)]);
+      }
+    }
+  }
+
+  /// Fewest samples any pixel has received.
+  pub fn min_samples(&self) -> u32 {
+    self.pixels.iter().map(|sum| sum.samples).min().unwrap_or(0)
+  }
+
+  /// Averages every pixel over the samples it actually received, weighted
+  /// by the reconstruction filter when splats are kept. Pixels without
+  /// samples stay black and transparent.
+  pub fn resolve(&self, transparent_background: bool) -> Framebuffer {
+    let mut image = Framebuffer::new(self.width, self.height);
+    for (index, sum) in self.pixels.iter().enumerate() {
+      image.sample_counts[index] = sum.samples;
+      if sum.samples == 0 {
+        continue;
+      }
+      let (radiance, covered, weight) = match self.splats.get(index).map(Splat::sums) {
+        // Negative lobes can leave a sparsely sampled pixel with next to
+        // no weight; its own samples are the better estimate then.
+        Some(splat) if splat.2 > 1e-6 => splat,
+        _ => (sum.radiance, sum.covered as f64, sum.samples as f64),
+      };
+      let scale = 1.0 / weight;
+      // Ringing from negative lobes is clipped rather than written out.
+      let color = scale * radiance;
+      image.pixels[index] = Color::from_values(color.x().max(0.0), color.y().max(0.0), color.z().max(0.0));
+      image.alpha[index] = if transparent_background { (covered * scale).clamp(0.0, 1.0) } else { 1.0 };
+    }
+    image
+  }
+}
@@ -11,6 +11,10 @@ pub struct HitRecord {
   pub mat: Option<Arc<dyn Material>>,
   pub t: f64,
   pub front_face: bool,
+  /// Time of the ray that made the hit, passed on to scattered rays.
+  pub time: f64,
+  /// Identity of the primitive that was hit, unique within a world.
+  pub object_key: usize,
 }
 
 impl HitRecord {
@@ -21,6 +25,8 @@ impl HitRecord {
       mat: None,
       t: 0.0,
       front_face: false,
+      time: 0.0,
+      object_key: 0,
     }
   }
 
@@ -34,6 +40,11 @@ impl HitRecord {
   }
 }
 
+impl Default for HitRecord {
+  fn default() -> Self {
+    Self::new()
+  }
+}
 
 pub trait Hittable: Send + Sync {
     fn hit(&self, ray: &Ray, ray_t: Interval, rec: &mut HitRecord) -> bool;
@@ -48,6 +59,8 @@ impl std::fmt::Debug for HitRecord {
       .field("mat", &self.mat.is_some())  
       .field("t", &self.t)
       .field("front_face", &self.front_face)
+      .field("time", &self.time)
+      .field("object_key", &self.object_key)
       .finish()
   }
 }
@@ -0,0 +1,438 @@
+use crate::color::{Color, TransferFunction};
+use crate::aov::AovLayer;
+use crate::exr::{write_exr, ExrChannel};
+use crate::framebuffer::Framebuffer;
+use crate::tonemap::DisplayTransform;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImageFormat {
+  /// Plain-text PPM (`P3`), one pixel per line.
+  PpmAscii,
+  /// Binary PPM (`P6`) with 8 bits per channel.
+  #[default]
+  Ppm8,
+  /// Binary PPM (`P6`) with 16 bits per channel, big-endian as the format requires.
+  Ppm16,
+  /// 8-bit RGB PNG.
+  Png8,
+  /// 16-bit RGB PNG.
+  Png16,
+  /// 8-bit RGBA PNG; alpha is the fraction of camera rays that hit geometry.
+  Png8Rgba,
+  /// 16-bit RGBA PNG; alpha is the fraction of camera rays that hit geometry.
+  Png16Rgba,
+  /// Portable float map: linear 32-bit float RGB, bottom row first.
+  Pfm,
+  /// Radiance RGBE (`.hdr`) with run-length encoded scanlines.
+  Hdr,
+  /// Uncompressed scanline OpenEXR with 32-bit float RGBA channels.
+  Exr,
+}
+
+impl ImageFormat {
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name.to_ascii_lowercase().as_str() {
+      "ppm-ascii" | "p3" => Some(ImageFormat::PpmAscii),
+      "ppm" | "ppm8" | "p6" => Some(ImageFormat::Ppm8),
+      "ppm16" => Some(ImageFormat::Ppm16),
+      "png" | "png8" => Some(ImageFormat::Png8),
+      "png16" => Some(ImageFormat::Png16),
+      "png-rgba" | "png8-rgba" => Some(ImageFormat::Png8Rgba),
+      "png16-rgba" => Some(ImageFormat::Png16Rgba),
+      "pfm" => Some(ImageFormat::Pfm),
+      "hdr" | "rgbe" => Some(ImageFormat::Hdr),
+      "exr" => Some(ImageFormat::Exr),
+      _ => None,
+    }
+  }
+
+  /// Picks the default format for a file from its extension.
+  pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+    let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+      "ppm" => Some(ImageFormat::Ppm8),
+      "png" => Some(ImageFormat::Png8),
+      "pfm" => Some(ImageFormat::Pfm),
+      "hdr" => Some(ImageFormat::Hdr),
+      "exr" => Some(ImageFormat::Exr),
+      _ => None,
+    }
+  }
+
+  pub fn extension(&self) -> &'static str {
+    match self {
+      ImageFormat::PpmAscii | ImageFormat::Ppm8 | ImageFormat::Ppm16 => "ppm",
+      ImageFormat::Png8 | ImageFormat::Png16 | ImageFormat::Png8Rgba | ImageFormat::Png16Rgba => "png",
+      ImageFormat::Pfm => "pfm",
+      ImageFormat::Hdr => "hdr",
+      ImageFormat::Exr => "exr",
+    }
+  }
+
+  /// Whether the format stores linear radiance as-is instead of clamped,
+  /// display-encoded values.
+  pub fn is_hdr(&self) -> bool {
+    matches!(self, ImageFormat::Pfm | ImageFormat::Hdr | ImageFormat::Exr)
+  }
+
+  pub fn has_alpha(&self) -> bool {
+    matches!(self, ImageFormat::Png8Rgba | ImageFormat::Png16Rgba)
+  }
+}
+
+/// Writes `image` in the requested format. Rows are encoded into a single
+/// reusable buffer and streamed through a `BufWriter`, so nothing is
+/// allocated per pixel. `display` maps radiance to display values for the
+/// LDR formats; HDR formats store the radiance untouched. Only RGBA PNG and
+/// EXR keep the alpha channel, and only EXR stores the AOV layers.
+pub fn write_image<W: Write>(
+  out: &mut W,
+  format: ImageFormat,
+  image: &Framebuffer,
+  display: &DisplayTransform,
+) -> io::Result<()> {
+  let mut out = BufWriter::new(out);
+  let (width, height) = (image.width, image.height);
+  let pixels = &image.pixels;
+
+  match format {
+    ImageFormat::PpmAscii => {
+      writeln!(out, "P3\n{} {}\n255", width, height)?;
+      for (index, color) in pixels.iter().enumerate() {
+        let [r, g, b] = display.encode(*color, 255, index % width, index / width);
+        writeln!(out, "{} {} {}", r, g, b)?;
+      }
+    }
+    ImageFormat::Ppm8 | ImageFormat::Ppm16 => {
+      let sixteen_bit = format == ImageFormat::Ppm16;
+      writeln!(out, "P6\n{} {}\n{}", width, height, if sixteen_bit { 65535 } else { 255 })?;
+
+      let mut row = Vec::new();
+      for y in 0..height {
+        encode_row(&mut row, image, y, false, sixteen_bit, display);
+        out.write_all(&row)?;
+      }
+    }
+    ImageFormat::Png8 | ImageFormat::Png16 | ImageFormat::Png8Rgba | ImageFormat::Png16Rgba => {
+      let sixteen_bit = matches!(format, ImageFormat::Png16 | ImageFormat::Png16Rgba);
+      let with_alpha = format.has_alpha();
+
+      let mut encoder = png::Encoder::new(&mut out, width as u32, height as u32);
+      encoder.set_color(if with_alpha { png::ColorType::Rgba } else { png::ColorType::Rgb });
+      encoder.set_depth(if sixteen_bit { png::BitDepth::Sixteen } else { png::BitDepth::Eight });
+      match display.transfer {
+        TransferFunction::Srgb => encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual),
+        transfer => {
+          if let Some(gamma) = transfer.png_gamma() {
+            encoder.set_source_gamma(png::ScaledFloat::new(gamma));
+          }
+        }
+      }
+      let mut png_writer = encoder.write_header()?;
+      let mut stream = png_writer.stream_writer()?;
+
+      let mut row = Vec::new();
+      for y in 0..height {
+        encode_row(&mut row, image, y, with_alpha, sixteen_bit, display);
+        stream.write_all(&row)?;
+      }
+      stream.finish()?;
+    }
+    ImageFormat::Pfm => {
+      // A negative scale marks little-endian samples.
+      writeln!(out, "PF\n{} {}\n-1.0", width, height)?;
+      let mut row = Vec::with_capacity(width * 12);
+      for line in pixels.chunks(width).rev() {
+        row.clear();
+        for color in line {
+          for channel in 0..3 {
+            row.extend_from_slice(&(color[channel] as f32).to_le_bytes());
+          }
+        }
+        out.write_all(&row)?;
+      }
+    }
+    ImageFormat::Hdr => {
+      write!(out, "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n", height, width)?;
+      let mut rgbe = Vec::with_capacity(width * 4);
+      let mut row = Vec::new();
+      for line in pixels.chunks(width) {
+        rgbe.clear();
+        rgbe.extend(line.iter().flat_map(float_to_rgbe));
+        encode_rgbe_row(&mut row, &rgbe, width);
+        out.write_all(&row)?;
+      }
+    }
+    ImageFormat::Exr => {
+      // EXR colour is premultiplied by convention, which is what the
+      // framebuffer already holds.
+      let plane = |channel: usize| -> Vec<f32> { pixels.iter().map(|c| c[channel] as f32).collect() };
+      let (red, green, blue) = (plane(0), plane(1), plane(2));
+      let alpha: Vec<f32> = image.alpha.iter().map(|&a| a as f32).collect();
+      let mut channels = vec![
+        ExrChannel { name: "R".to_string(), data: &red },
+        ExrChannel { name: "G".to_string(), data: &green },
+        ExrChannel { name: "B".to_string(), data: &blue },
+        ExrChannel { name: "A".to_string(), data: &alpha },
+      ];
+      channels.extend(image.aovs.iter().flat_map(AovLayer::exr_channels));
+      write_exr(&mut out, width, height, &mut channels)?;
+    }
+  }
+
+  out.flush()
+}
+
+/// Encodes row `y` of `image` into big-endian 8- or 16-bit samples through
+/// the display transform. With `with_alpha` the colour is un-premultiplied
+/// and followed by a linear alpha sample per pixel.
+fn encode_row(
+  row: &mut Vec<u8>,
+  image: &Framebuffer,
+  y: usize,
+  with_alpha: bool,
+  sixteen_bit: bool,
+  display: &DisplayTransform,
+) {
+  let max_value: u32 = if sixteen_bit { 65535 } else { 255 };
+  let push = |row: &mut Vec<u8>, value: u32| {
+    if sixteen_bit {
+      row.extend_from_slice(&(value as u16).to_be_bytes());
+    } else {
+      row.push(value as u8);
+    }
+  };
+
+  row.clear();
+  for x in 0..image.width {
+    let index = image.index(x, y);
+    if with_alpha {
+      for code in display.encode(image.straight_color(index), max_value, x, y) {
+        push(row, code);
+      }
+      // Alpha is coverage, not a colour, so it is stored linearly.
+      let coverage = image.alpha[index].clamp(0.0, 1.0);
+      push(row, (coverage * max_value as f64).round() as u32);
+    } else {
+      for code in display.encode(image.pixels[index], max_value, x, y) {
+        push(row, code);
+      }
+    }
+  }
+}
+
+/// Writes the per-pixel sample counts of `image` as an 8-bit PNG heatmap,
+/// from dark blue (no samples) through green to dark red (the most samples).
+pub fn write_sample_heatmap<W: Write>(out: &mut W, image: &Framebuffer) -> io::Result<()> {
+  let mut out = BufWriter::new(out);
+  let max = image.sample_counts.iter().copied().max().unwrap_or(0).max(1) as f64;
+
+  {
+    let mut encoder = png::Encoder::new(&mut out, image.width as u32, image.height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+    let mut png_writer = encoder.write_header()?;
+    let mut stream = png_writer.stream_writer()?;
+
+    let mut row = Vec::with_capacity(image.width * 3);
+    for line in image.sample_counts.chunks(image.width) {
+      row.clear();
+      for &count in line {
+        row.extend(turbo(count as f64 / max).map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8));
+      }
+      stream.write_all(&row)?;
+    }
+    stream.finish()?;
+  }
+  out.flush()
+}
+
+/// Polynomial fit of Google's Turbo colormap, returning sRGB-encoded values.
+fn turbo(x: f64) -> [f64; 3] {
+  const RED: [f64; 6] = [0.13572138, 4.61539260, -42.66032258, 132.13108234, -152.94239396, 59.28637943];
+  const GREEN: [f64; 6] = [0.09140261, 2.19418839, 4.84296658, -14.18503333, 4.27729857, 2.82956604];
+  const BLUE: [f64; 6] = [0.10667330, 12.64194608, -60.58204836, 110.36276771, -89.90310912, 27.34824973];
+
+  let x = x.clamp(0.0, 1.0);
+  let eval = |k: &[f64; 6]| k.iter().rev().fold(0.0, |acc, &c| acc * x + c);
+  [eval(&RED), eval(&GREEN), eval(&BLUE)]
+}
+
+/// Shared-exponent encoding used by Radiance: the largest component's
+/// exponent is stored once and the mantissas are scaled to fit a byte.
+fn float_to_rgbe(color: &Color) -> [u8; 4] {
+  let (r, g, b) = (color.x().max(0.0), color.y().max(0.0), color.z().max(0.0));
+  let v = r.max(g).max(b);
+  if v < 1e-32 {
+    return [0, 0, 0, 0];
+  }
+
+  // v = m * 2^e with m in [0.5, 1)
+  let mut e = v.log2().floor() as i32 + 1;
+  let mut m = v / 2f64.powi(e);
+  if m >= 1.0 {
+    m /= 2.0;
+    e += 1;
+  } else if m < 0.5 {
+    m *= 2.0;
+    e -= 1;
+  }
+  let scale = m * 256.0 / v;
+  [(r * scale) as u8, (g * scale) as u8, (b * scale) as u8, (e + 128) as u8]
+}
+
+/// Encodes one scanline of RGBE pixels using the "new" Radiance run-length
+/// scheme, where each component is compressed separately. Widths outside
+/// the range the scheme supports are written flat.
+fn encode_rgbe_row(row: &mut Vec<u8>, rgbe: &[u8], width: usize) {
+  const MIN_RUN: usize = 4;
+
+  row.clear();
+  if !(8..32768).contains(&width) {
+    row.extend_from_slice(rgbe);
+    return;
+  }
+  row.extend_from_slice(&[2, 2, (width >> 8) as u8, (width & 0xff) as u8]);
+
+  let mut data = Vec::with_capacity(width);
+  for component in 0..4 {
+    data.clear();
+    data.extend(rgbe.iter().skip(component).step_by(4));
+
+    let mut cur = 0;
+    while cur < width {
+      // Find the start of the next run of at least MIN_RUN equal bytes.
+      let mut beg_run = cur;
+      let mut run_count = 0;
+      let mut old_run_count = 0;
+      while run_count < MIN_RUN && beg_run < width {
+        beg_run += run_count;
+        old_run_count = run_count;
+        run_count = 1;
+        while beg_run + run_count < width && run_count < 127 && data[beg_run] == data[beg_run + run_count] {
+          run_count += 1;
+        }
+      }
+
+      // A short run right before the long one is still worth encoding.
+      if old_run_count > 1 && old_run_count == beg_run - cur {
+        row.extend_from_slice(&[128 + old_run_count as u8, data[cur]]);
+        cur = beg_run;
+      }
+
+      while cur < beg_run {
+        let count = (beg_run - cur).min(128);
+        row.push(count as u8);
+        row.extend_from_slice(&data[cur..cur + count]);
+        cur += count;
+      }
+
+      if run_count >= MIN_RUN {
+        row.extend_from_slice(&[128 + run_count as u8, data[beg_run]]);
+        cur += run_count;
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::color::quantize;
+
+  /// Black and white on the top row, red and mid grey below.
+  fn test_image() -> Framebuffer {
+    let mut image = Framebuffer::new(2, 2);
+    image.pixels = vec![
+      Color::from_values(0.0, 0.0, 0.0),
+      Color::from_values(1.0, 1.0, 1.0),
+      Color::from_values(1.0, 0.0, 0.0),
+      Color::from_values(0.5, 0.5, 0.5),
+    ];
+    image.alpha = vec![1.0, 1.0, 1.0, 0.5];
+    image
+  }
+
+  fn encode(format: ImageFormat, image: &Framebuffer) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    write_image(&mut bytes, format, image, &DisplayTransform::default()).unwrap();
+    bytes
+  }
+
+  /// The 8-bit sRGB code of linear 0.5.
+  const GREY: u8 = 188;
+
+  #[test]
+  fn p6_rows_run_top_down() {
+    let bytes = encode(ImageFormat::Ppm8, &test_image());
+    let header = b"P6\n2 2\n255\n";
+    assert_eq!(&bytes[..header.len()], header);
+    assert_eq!(&bytes[header.len()..], &[0, 0, 0, 255, 255, 255, 255, 0, 0, GREY, GREY, GREY]);
+  }
+
+  #[test]
+  fn p6_sixteen_bit_samples_are_big_endian() {
+    let bytes = encode(ImageFormat::Ppm16, &test_image());
+    let header = b"P6\n2 2\n65535\n";
+    assert_eq!(&bytes[..header.len()], header);
+    let samples = &bytes[header.len()..];
+    assert_eq!(samples.len(), 2 * 2 * 3 * 2);
+    // Red, the first pixel of the second row.
+    assert_eq!(&samples[12..18], &[0xff, 0xff, 0, 0, 0, 0]);
+    let grey = quantize(TransferFunction::Srgb.encode(0.5), 65535, 0.0) as u16;
+    assert_eq!(&samples[18..20], &grey.to_be_bytes());
+  }
+
+  fn decode_png(bytes: &[u8]) -> (png::OutputInfo, Vec<u8>) {
+    let mut reader = png::Decoder::new(bytes).read_info().unwrap();
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut data).unwrap();
+    data.truncate(info.buffer_size());
+    (info, data)
+  }
+
+  #[test]
+  fn png_round_trips() {
+    let (info, data) = decode_png(&encode(ImageFormat::Png8, &test_image()));
+    assert_eq!((info.width, info.height), (2, 2));
+    assert_eq!((info.color_type, info.bit_depth), (png::ColorType::Rgb, png::BitDepth::Eight));
+    assert_eq!(data, [0, 0, 0, 255, 255, 255, 255, 0, 0, GREY, GREY, GREY]);
+
+    let (info, data) = decode_png(&encode(ImageFormat::Png16Rgba, &test_image()));
+    assert_eq!((info.color_type, info.bit_depth), (png::ColorType::Rgba, png::BitDepth::Sixteen));
+    assert_eq!(data.len(), 2 * 2 * 4 * 2);
+    // White is opaque; the grey pixel has half coverage, stored linearly
+    // after un-premultiplying its colour back to 1.0.
+    assert_eq!(&data[8..16], &[0xff; 8]);
+    assert_eq!(&data[24..32], &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x80, 0x00]);
+  }
+
+  #[test]
+  fn pfm_rows_run_bottom_up_in_little_endian() {
+    let bytes = encode(ImageFormat::Pfm, &test_image());
+    let header = b"PF\n2 2\n-1.0\n";
+    assert_eq!(&bytes[..header.len()], header);
+    let floats: Vec<f32> = bytes[header.len()..]
+      .chunks(4)
+      .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+      .collect();
+    assert_eq!(floats, [1.0, 0.0, 0.0, 0.5, 0.5, 0.5, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0]);
+  }
+
+  #[test]
+  fn rgbe_shares_the_largest_exponent() {
+    // 1.0 = 0.5 * 2^1, so the mantissas are scaled by 256 / 2.
+    assert_eq!(float_to_rgbe(&Color::from_values(1.0, 0.5, 0.25)), [128, 64, 32, 129]);
+    assert_eq!(float_to_rgbe(&Color::from_values(0.0, 3.0, 0.0)), [0, 192, 0, 130]);
+    assert_eq!(float_to_rgbe(&Color::from_values(0.0, 0.0, 0.0)), [0, 0, 0, 0]);
+
+    // Scanlines narrower than 8 pixels are stored flat.
+    let bytes = encode(ImageFormat::Hdr, &test_image());
+    let header = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 2 +X 2\n";
+    assert_eq!(&bytes[..header.len()], header);
+    assert_eq!(&bytes[header.len()..header.len() + 8], &[0, 0, 0, 0, 128, 128, 128, 129]);
+  }
+}
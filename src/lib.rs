@@ -0,0 +1,27 @@
+pub mod vec3;
+pub mod color;
+pub mod image_writer;
+pub mod exr;
+pub mod framebuffer;
+pub mod filter;
+pub mod checkpoint;
+pub mod tonemap;
+pub mod blue_noise;
+pub mod sampler;
+pub mod aov;
+pub mod ray;
+pub mod hittable;
+pub mod sphere;
+pub mod hittable_list;
+pub mod rtweekend;
+pub mod interval;
+pub mod camera;
+pub mod projection;
+pub mod stereo;
+pub mod cancel;
+pub mod progress;
+pub mod tiles;
+pub mod material;
+pub mod bvh;
+pub mod animation;
+pub mod scenes;
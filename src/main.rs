@@ -1,23 +1,126 @@
 use std::io::{self, Write};
+use std::process;
+use std::sync::Arc;
 
-mod vec3;
-mod color;
-mod ray;
-mod hittable;
-mod sphere;
-mod hittable_list;
-mod rtweekend;
-mod interval;
-mod camera;
-mod material;
-mod bvh;
-mod scenes;
+use rustytracer::animation::{load_camera_path, CameraAnimation, Orbit};
+use rustytracer::cancel::CancelToken;
+use rustytracer::checkpoint::load_checkpoint;
+use rustytracer::scenes::{self, Scene, SceneOptions};
+use signal_hook::consts::SIGINT;
+
+mod cli;
+
+use cli::{Command, RenderArgs};
 
 fn main() -> io::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = match cli::parse_args(&args) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!(" Error: {}", e);
+            eprintln!("Run 'raytracer help' for usage.");
+            process::exit(2);
+        }
+    };
+
+    match command {
+        Command::Interactive => run_interactive(),
+        Command::ListScenes => {
+            list_scenes();
+            Ok(())
+        }
+        Command::Render(args) => {
+            if let Err(e) = render(&args) {
+                eprintln!(" Error rendering scene: {}", e);
+                process::exit(1);
+            }
+            Ok(())
+        }
+        Command::Help => {
+            println!("{}", cli::USAGE);
+            Ok(())
+        }
+    }
+}
+
+fn render(args: &RenderArgs) -> io::Result<()> {
+    let checkpoint = match &args.resume {
+        Some(path) => Some(load_checkpoint(path)?),
+        None => None,
+    };
+    let name = match (&args.scene, &checkpoint) {
+        (Some(name), _) => name.as_str(),
+        (None, Some(checkpoint)) => checkpoint.scene.as_str(),
+        (None, None) => unreachable!("the parser requires --scene or --resume"),
+    };
+    let Some(scene) = scenes::find(name) else {
+        eprintln!(" Unknown scene '{}'. Available scenes:", name);
+        for scene in scenes::all() {
+            eprintln!("  {}", scene.name());
+        }
+        process::exit(2);
+    };
+
+    let mut options = args.options.clone();
+    handle_interrupts(&options.cancel)?;
+
+    if let Some(animation) = &args.animation {
+        let frames = animation.frames.clone();
+        let camera_animation = CameraAnimation {
+            path: match &animation.keyframes {
+                Some(path) => Some(load_camera_path(path, animation.interpolation)?),
+                None => None,
+            },
+            orbit: animation.orbit.map(|degrees| Orbit {
+                degrees,
+                first_frame: *frames.start(),
+                frames: frames.clone().count() as u32,
+            }),
+        };
+        println!("Rendering {} frames {} to {}...", scene.title(), frames.start(), frames.end());
+        return scenes::render_animation(scene, &options, &camera_animation, frames);
+    }
+
+    match checkpoint {
+        Some(checkpoint) => {
+            // Keep checkpointing into the file being resumed unless told otherwise.
+            if options.checkpoint.is_none() {
+                options.checkpoint = args.resume.clone();
+            }
+            println!("Resuming {}...", scene.title());
+            scenes::resume_scene(scene, &options, checkpoint)
+        }
+        None => {
+            println!("Rendering {}...", scene.title());
+            scenes::render_scene(scene, &options)
+        }
+    }
+}
+
+/// The first Ctrl-C cancels the render so the partial image (and checkpoint)
+/// can still be written; a second one exits straight away.
+fn handle_interrupts(cancel: &CancelToken) -> io::Result<()> {
+    let flag = Arc::clone(cancel.flag());
+    // Registered first, so it only fires once the flag below is already set.
+    signal_hook::flag::register_conditional_shutdown(SIGINT, 130, Arc::clone(&flag))?;
+    signal_hook::flag::register(SIGINT, flag)?;
+    Ok(())
+}
+
+fn list_scenes() {
+    for scene in scenes::all() {
+        println!("{:<18} {}", scene.name(), scene.description());
+    }
+}
+
+fn run_interactive() -> io::Result<()> {
+    let options = SceneOptions::default();
+    handle_interrupts(&options.cancel)?;
+    let registry = scenes::all();
     display_welcome();
     
     loop {
-        display_menu();
+        display_menu(registry);
         
         print!("Enter your choice: ");
         io::stdout().flush()?;
@@ -25,70 +128,32 @@ fn main() -> io::Result<()> {
         let mut input = String::new();
         io::stdin().read_line(&mut input)?;
         
-        match input.trim() {
-            "1" => {
-                println!("Rendering Original Scene...");
-                if let Err(e) = scenes::create_original_scene() {
-                    eprintln!(" Error rendering scene: {}", e);
-                }
-            }
-            "2" => {
-                println!("Rendering Spiral Galaxy...");
-                if let Err(e) = scenes::create_spiral_scene() {
-                    eprintln!(" Error rendering scene: {}", e);
-                }
-            }
-            "3" => {
-                println!("Rendering Crystal Cave...");
-                if let Err(e) = scenes::create_crystal_scene() {
-                    eprintln!(" Error rendering scene: {}", e);
-                }
-            }
-            "4" => {
-                println!("Rendering Planetary Rings...");
-                if let Err(e) = scenes::create_planetary_rings_scene() {
-                    eprintln!(" Error rendering scene: {}", e);
-                }
-            }
-            "5" => {
-                println!("Rendering Bubble Garden...");
-                if let Err(e) = scenes::create_bubble_garden_scene() {
-                    eprintln!(" Error rendering scene: {}", e);
-                }
-            }
-            "6" => {
-                println!("Rendering Enhanced Spiral Galaxy...");
-                if let Err(e) = scenes::create_enhanced_spiral_scene() {
-                    eprintln!(" Error rendering scene: {}", e);
-                }
-            }
-            "7" => {
-                println!("Rendering Enhanced Crystal Cave...");
-                if let Err(e) = scenes::create_enhanced_crystal_scene() {
-                    eprintln!(" Error rendering scene: {}", e);
-                }
-            }
-            "8" => {
-                println!("Rendering Enhanced Planetary Rings...");
-                if let Err(e) = scenes::create_enhanced_rings_scene() {
-                    eprintln!(" Error rendering scene: {}", e);
-                }
-            }
+        let scene: &dyn Scene = match input.trim() {
             "q" | "Q" | "quit" | "exit" => {
                 println!("Exiting!!");
                 break;
             }
             "" => {
-                println!("Rendering Original Scene (default)...");
-                if let Err(e) = scenes::create_original_scene() {
-                    eprintln!(" Error rendering scene: {}", e);
-                }
-            }
-            _ => {
-                println!(" Choose from the Menu Nigga!");
-                continue;
+                println!("Rendering {} Scene (default)...", registry[0].title());
+                registry[0]
             }
+            choice => match choice.parse::<usize>() {
+                Ok(n) if (1..=registry.len()).contains(&n) => {
+                    println!("Rendering {}...", registry[n - 1].title());
+                    registry[n - 1]
+                }
+                _ => {
+                    println!(" Choose from the menu!");
+                    continue;
+                }
+            },
+        };
+
+        if let Err(e) = scenes::render_scene(scene, &options) {
+            eprintln!(" Error rendering scene: {}", e);
         }
+        // Ready for the next render, where the first Ctrl-C cancels again.
+        options.cancel.reset();
         
         println!("\n{}", "=".repeat(50));
         print!("Press Enter to continue or 'q' to quit: ");
@@ -112,19 +177,14 @@ fn display_welcome() {
     
 }
 
-fn display_menu() {
+fn display_menu(registry: &[&dyn Scene]) {
     println!("\n AVAILABLE SCENES:");
-    println!("1. Original");
-    println!("2. Spiral Galaxy");
-    println!("3. Crystal Cave");
-    println!("4. Planetary Rings");
-    println!("5. Bubble Garden");
-    println!("6. Enhanced Spiral");
-    println!("7. Enhanced Crystal");
-    println!("8. Enhanced Rings");
+    for (i, scene) in registry.iter().enumerate() {
+        println!("{}. {}", i + 1, scene.title());
+    }
     println!("  ");
     println!("  ");
-    println!("  => Press Enter (default) for Original Scene");
+    println!("  => Press Enter (default) for {} Scene", registry[0].title());
     println!("  => Type 'q' to quit");
     println!();
-}
\ No newline at end of file
+}
@@ -1,17 +1,25 @@
-use crate::rtweekend::random_float;
-use crate::vec3::{dot, random_unit_vector, reflect, refract, unit_vector};
+use crate::vec3::{dot, reflect, refract, sample_unit_vector, unit_vector};
 use crate::ray::Ray;
 use crate::hittable::HitRecord;
 use crate::color::Color;
+use crate::sampler::Sampler;
 
 pub trait Material: Send + Sync {
+    /// Random decisions draw from `sampler`, so stratified and
+    /// low-discrepancy samplers reach the bounces too.
     fn scatter(
       &self,
       r_in: &Ray,
       rec: &HitRecord,
       attenuation: &mut Color,
       scattered: &mut Ray,
+      sampler: &mut dyn Sampler,
     ) -> bool;
+
+    /// Surface reflectance, as reported in the albedo AOV.
+    fn albedo(&self) -> Color {
+      Color::from_values(1.0, 1.0, 1.0)
+    }
 }
 
 pub struct Lambertian {
@@ -31,23 +39,29 @@ impl Material for Lambertian {
         rec: &HitRecord,
         attenuation: &mut Color,
         scattered: &mut Ray,
+        sampler: &mut dyn Sampler,
     ) -> bool {
         
         let albedo_max = self.albedo.x().max(self.albedo.y()).max(self.albedo.z());
-        if random_float() > albedo_max {
+        if sampler.get_1d() > albedo_max {
             return false;
         }
 
-        let mut scatter_direction = rec.normal + random_unit_vector();
+        let (u, v) = sampler.get_2d();
+        let mut scatter_direction = rec.normal + sample_unit_vector(u, v);
 
         if scatter_direction.near_zero() {
             scatter_direction = rec.normal;
         }
 
-        *scattered = Ray::from_origin_direction(rec.p, scatter_direction);
+        *scattered = Ray::from_origin_direction_time(rec.p, scatter_direction, rec.time);
         *attenuation = self.albedo / albedo_max; 
         true
     }
+
+    fn albedo(&self) -> Color {
+        self.albedo
+    }
 }
 
 pub struct Metal {
@@ -71,14 +85,20 @@ impl Material for Metal {
         rec: &HitRecord,
         attenuation: &mut Color,
         scattered: &mut Ray,
+        sampler: &mut dyn Sampler,
       ) -> bool {
       let reflected = reflect(r_in.direction(), &rec.normal);
-      let reflected = unit_vector(&reflected) + (self.fuzz * random_unit_vector());
-      *scattered = Ray::from_origin_direction(rec.p, reflected);
+      let (u, v) = sampler.get_2d();
+      let reflected = unit_vector(&reflected) + (self.fuzz * sample_unit_vector(u, v));
+      *scattered = Ray::from_origin_direction_time(rec.p, reflected, rec.time);
       *attenuation = self.albedo;
       
       dot(scattered.direction(), &rec.normal) > 0.0
   }
+
+  fn albedo(&self) -> Color {
+    self.albedo
+  }
 }
 
 pub struct Dielectric {
@@ -103,6 +123,7 @@ impl Material for Dielectric {
         rec: &HitRecord,
         attenuation: &mut Color,
         scattered: &mut Ray,
+        sampler: &mut dyn Sampler,
       ) -> bool {
       *attenuation = Color::from_values(1.0, 1.0, 1.0);
       let ri = if rec.front_face {
@@ -115,14 +136,16 @@ impl Material for Dielectric {
       let cos_theta = dot(&(-unit_direction), &rec.normal).min(1.0);
       let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
 
+      // Always drawn, so later bounces stay on the same sampler dimensions.
+      let u = sampler.get_1d();
       let cannot_refract = ri * sin_theta > 1.0;
-      let direction = if cannot_refract || Self::reflectance(cos_theta, ri) > random_float() {
+      let direction = if cannot_refract || Self::reflectance(cos_theta, ri) > u {
         reflect(&unit_direction, &rec.normal)
       } else {
         refract(&unit_direction, &rec.normal, ri)
       };
       
-      *scattered = Ray::from_origin_direction(rec.p, direction);
+      *scattered = Ray::from_origin_direction_time(rec.p, direction, rec.time);
       true
   }
 }
\ No newline at end of file
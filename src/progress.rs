@@ -0,0 +1,172 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Thread-safe counters for a running render. Workers add to them as tiles
+/// finish; anyone may take a `Progress` snapshot at any time.
+#[derive(Debug)]
+pub struct ProgressTracker {
+  start: Instant,
+  total_samples: u64,
+  /// Samples that were already there when the render (re)started; they do
+  /// not count towards the sampling rate.
+  initial_samples: u64,
+  time_budget: Option<Duration>,
+  samples: AtomicU64,
+  rays: AtomicU64,
+  finished: AtomicBool,
+}
+
+impl ProgressTracker {
+  pub fn new(total_samples: u64, initial_samples: u64, time_budget: Option<Duration>) -> Self {
+    ProgressTracker {
+      start: Instant::now(),
+      total_samples,
+      initial_samples,
+      time_budget,
+      samples: AtomicU64::new(initial_samples),
+      rays: AtomicU64::new(0),
+      finished: AtomicBool::new(false),
+    }
+  }
+
+  pub fn add(&self, samples: u64, rays: u64) {
+    self.samples.fetch_add(samples, Ordering::Relaxed);
+    self.rays.fetch_add(rays, Ordering::Relaxed);
+  }
+
+  /// Marks the render as over, whether it completed or was stopped early.
+  pub fn finish(&self) {
+    self.finished.store(true, Ordering::Relaxed);
+  }
+
+  pub fn report(&self) -> Progress {
+    let elapsed = self.start.elapsed();
+    let samples = self.samples.load(Ordering::Relaxed).min(self.total_samples);
+    let rays = self.rays.load(Ordering::Relaxed);
+    let seconds = elapsed.as_secs_f64();
+    let finished = self.finished.load(Ordering::Relaxed);
+
+    let mut fraction = if self.total_samples > 0 { samples as f64 / self.total_samples as f64 } else { 1.0 };
+    let sampled = samples.saturating_sub(self.initial_samples);
+    let mut eta = (sampled > 0 && seconds > 0.0)
+      .then(|| Duration::from_secs_f64((self.total_samples - samples) as f64 * seconds / sampled as f64));
+    if let Some(budget) = self.time_budget {
+      // Budgeted renders end when time runs out, whichever comes first.
+      fraction = fraction.max((seconds / budget.as_secs_f64()).min(1.0));
+      let left = budget.saturating_sub(elapsed);
+      eta = Some(eta.map_or(left, |eta| eta.min(left)));
+    }
+
+    if finished {
+      eta = Some(Duration::ZERO);
+    }
+
+    Progress {
+      samples,
+      total_samples: self.total_samples,
+      rays,
+      elapsed,
+      fraction,
+      eta,
+      rays_per_second: if seconds > 0.0 { rays as f64 / seconds } else { 0.0 },
+      finished,
+    }
+  }
+}
+
+/// A snapshot of a render's progress.
+#[derive(Debug, Clone, Copy)]
+pub struct Progress {
+  /// Camera samples taken so far, including any resumed from a checkpoint.
+  pub samples: u64,
+  /// Samples the render will take if it runs to completion.
+  pub total_samples: u64,
+  /// Rays traced so far in this run, bounces included.
+  pub rays: u64,
+  pub elapsed: Duration,
+  /// Completion from 0 to 1.
+  pub fraction: f64,
+  /// Estimated time left, once there is a rate to estimate from.
+  pub eta: Option<Duration>,
+  pub rays_per_second: f64,
+  /// Set on the last report of a render.
+  pub finished: bool,
+}
+
+impl Progress {
+  /// One-line bar, e.g. `[#####-----]  50.0%  00:12 elapsed  ETA 00:12  1.52 Mrays/s`.
+  pub fn bar(&self, width: usize) -> String {
+    let filled = ((self.fraction * width as f64).round() as usize).min(width);
+    let eta = self.eta.map_or_else(|| "--:--".to_string(), format_duration);
+    format!(
+      "[{}{}] {:5.1}%  {} elapsed  ETA {}  {:.2} Mrays/s",
+      "#".repeat(filled),
+      "-".repeat(width - filled),
+      self.fraction * 100.0,
+      format_duration(self.elapsed),
+      eta,
+      self.rays_per_second / 1e6
+    )
+  }
+}
+
+/// Draws `Progress` reports as a bar on one line of stderr. Safe to update
+/// from every worker; redraws are limited to a few per second.
+#[derive(Debug)]
+pub struct ProgressBar {
+  width: usize,
+  last_draw: Mutex<Option<Instant>>,
+}
+
+impl ProgressBar {
+  const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+  pub fn new() -> Self {
+    ProgressBar { width: 30, last_draw: Mutex::new(None) }
+  }
+
+  pub fn update(&self, progress: &Progress) {
+    if progress.finished {
+      // The final report is always drawn, and ends the line.
+      *self.last_draw.lock().unwrap() = None;
+      eprintln!("\r{}", progress.bar(self.width));
+      return;
+    }
+    // A worker that finds another one drawing just skips this update.
+    let Ok(mut last_draw) = self.last_draw.try_lock() else {
+      return;
+    };
+    if last_draw.is_some_and(|last| last.elapsed() < Self::REDRAW_INTERVAL) {
+      return;
+    }
+    *last_draw = Some(Instant::now());
+    eprint!("\r{}", progress.bar(self.width));
+  }
+
+  /// Blanks the bar's line so other output can be printed over it; the next
+  /// update draws the bar again.
+  pub fn clear(&self) {
+    let mut last_draw = self.last_draw.lock().unwrap();
+    if last_draw.take().is_some() {
+      eprint!("\r{:1$}\r", "", self.width + 60);
+    }
+  }
+}
+
+impl Default for ProgressBar {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// `mm:ss`, or `h:mm:ss` from an hour up.
+pub fn format_duration(duration: Duration) -> String {
+  let total = duration.as_secs();
+  let (hours, minutes, seconds) = (total / 3600, (total / 60) % 60, total % 60);
+  if hours > 0 {
+    format!("{}:{:02}:{:02}", hours, minutes, seconds)
+  } else {
+    format!("{:02}:{:02}", minutes, seconds)
+  }
+}
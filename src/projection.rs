@@ -0,0 +1,127 @@
+use std::f64::consts::PI;
+
+use crate::rtweekend::degrees_to_radians;
+use crate::vec3::Vec3;
+
+/// How the camera turns image positions into rays.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Projection {
+  /// Pinhole or thin lens, with the field of view set by `vfov`.
+  #[default]
+  Perspective,
+  /// Parallel rays along the view direction, for technical and isometric
+  /// views. `view_width` is the width of the visible area in world units;
+  /// 0 picks the width the perspective view has at `focus_dist`.
+  Orthographic { view_width: f64 },
+  /// Latitude-longitude panorama covering every direction, with the view
+  /// direction in the middle of a 2:1 image.
+  Equirectangular,
+  /// The six 90° faces of a cube around the camera in a 3:2 grid: right,
+  /// left and up on the top row; down, front and back below.
+  CubeMap,
+  /// Circular fisheye filling a square image, `fov` degrees across (up
+  /// to 360).
+  Fisheye { mapping: FisheyeMapping, fov: f64 },
+}
+
+/// How a fisheye spreads angles from the view direction over its circle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FisheyeMapping {
+  /// Radius proportional to the angle, as used for dome masters.
+  #[default]
+  Equidistant,
+  /// Equal solid angles get equal areas of the image.
+  Equisolid,
+}
+
+impl Projection {
+  /// Projection called `name`, with its default parameters.
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name.to_ascii_lowercase().as_str() {
+      "perspective" => Some(Projection::Perspective),
+      "orthographic" | "ortho" => Some(Projection::Orthographic { view_width: 0.0 }),
+      "equirectangular" | "latlong" | "360" => Some(Projection::Equirectangular),
+      "cubemap" | "cube-map" | "cube" => Some(Projection::CubeMap),
+      "fisheye" | "fisheye-equidistant" => Some(Projection::Fisheye { mapping: FisheyeMapping::Equidistant, fov: 180.0 }),
+      "fisheye-equisolid" => Some(Projection::Fisheye { mapping: FisheyeMapping::Equisolid, fov: 180.0 }),
+      _ => None,
+    }
+  }
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      Projection::Perspective => "perspective",
+      Projection::Orthographic { .. } => "orthographic",
+      Projection::Equirectangular => "equirectangular",
+      Projection::CubeMap => "cubemap",
+      Projection::Fisheye { mapping: FisheyeMapping::Equidistant, .. } => "fisheye",
+      Projection::Fisheye { mapping: FisheyeMapping::Equisolid, .. } => "fisheye-equisolid",
+    }
+  }
+
+  /// Panoramic projections see in all directions from the camera centre
+  /// rather than through a viewport; they ignore `vfov` and depth of field.
+  pub fn is_panoramic(&self) -> bool {
+    !matches!(self, Projection::Perspective | Projection::Orthographic { .. })
+  }
+
+  /// The aspect ratio a panoramic projection needs, which replaces the
+  /// camera's own.
+  pub fn aspect_ratio(&self) -> Option<f64> {
+    match self {
+      Projection::Perspective | Projection::Orthographic { .. } => None,
+      Projection::Equirectangular => Some(2.0),
+      Projection::CubeMap => Some(1.5),
+      Projection::Fisheye { .. } => Some(1.0),
+    }
+  }
+
+  /// Direction seen at image position (s, t), both in `[0, 1]` with t
+  /// running down the image, in camera space: x right, y up, z along the
+  /// view direction. `None` for positions outside a fisheye's circle and
+  /// for the non-panoramic projections.
+  pub fn direction(&self, s: f64, t: f64) -> Option<Vec3> {
+    match *self {
+      Projection::Perspective | Projection::Orthographic { .. } => None,
+      Projection::Equirectangular => {
+        let longitude = (s - 0.5) * 2.0 * PI;
+        let latitude = (0.5 - t) * PI;
+        Some(Vec3::from_values(
+          latitude.cos() * longitude.sin(),
+          latitude.sin(),
+          latitude.cos() * longitude.cos(),
+        ))
+      }
+      Projection::CubeMap => {
+        let column = ((s * 3.0) as usize).min(2);
+        let row = ((t * 2.0) as usize).min(1);
+        // Position within the face, from -1 to 1, up being positive.
+        let a = 2.0 * (s * 3.0 - column as f64) - 1.0;
+        let b = 1.0 - 2.0 * (t * 2.0 - row as f64);
+        let direction = match (row, column) {
+          (0, 0) => Vec3::from_values(1.0, b, -a),
+          (0, 1) => Vec3::from_values(-1.0, b, a),
+          (0, _) => Vec3::from_values(a, 1.0, -b),
+          (_, 0) => Vec3::from_values(a, -1.0, b),
+          (_, 1) => Vec3::from_values(a, b, 1.0),
+          (_, _) => Vec3::from_values(-a, b, -1.0),
+        };
+        Some(direction)
+      }
+      Projection::Fisheye { mapping, fov } => {
+        let (x, y) = (2.0 * s - 1.0, 1.0 - 2.0 * t);
+        let radius = (x * x + y * y).sqrt();
+        if radius > 1.0 {
+          return None;
+        }
+        let max_angle = degrees_to_radians(fov.clamp(1.0, 360.0)) / 2.0;
+        let angle = match mapping {
+          FisheyeMapping::Equidistant => radius * max_angle,
+          FisheyeMapping::Equisolid => 2.0 * (radius * (max_angle / 2.0).sin()).asin(),
+        };
+        let azimuth = y.atan2(x);
+        Some(Vec3::from_values(angle.sin() * azimuth.cos(), angle.sin() * azimuth.sin(), angle.cos()))
+      }
+    }
+  }
+}
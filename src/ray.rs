@@ -4,15 +4,21 @@ use crate::vec3::{Vec3, Point3};
 pub struct Ray {
   origin : Point3,
   direction: Vec3,
+  /// Moment within the shutter interval the ray samples.
+  time: f64,
 }
 
 impl Ray {
   pub fn new() -> Self {
-    Ray { origin: Point3::new(), direction: Vec3::new() }
+    Ray { origin: Point3::new(), direction: Vec3::new(), time: 0.0 }
   }
 
   pub fn from_origin_direction(origin: Point3, direction: Vec3) -> Self {
-    Ray { origin: origin, direction: direction }
+    Ray { origin, direction, time: 0.0 }
+  }
+
+  pub fn from_origin_direction_time(origin: Point3, direction: Vec3, time: f64) -> Self {
+    Ray { origin, direction, time }
   }
 
   pub fn origin(&self) -> &Point3 {
@@ -23,6 +29,10 @@ impl Ray {
     &self.direction
   }
 
+  pub fn time(&self) -> f64 {
+    self.time
+  }
+
   pub fn at(&self, t: f64) -> Point3 {
     self.origin + t * self.direction
   }
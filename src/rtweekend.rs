@@ -1,14 +1,26 @@
-use rand::Rng;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use std::cell::RefCell;
+
 pub const INFINITY: f64 = f64::INFINITY;
-pub const PI: f64 = 3.1415926535897932385;
+pub const PI: f64 = std::f64::consts::PI;
+
+thread_local! {
+  static RNG: RefCell<SmallRng> = RefCell::new(SmallRng::from_rng(&mut rand::rng()));
+}
 
 pub fn degrees_to_radians(degrees: f64) -> f64 {
   degrees * PI / 180.0
 }
 
+/// Reseeds the calling thread's generator so that everything drawn from it
+/// afterwards (e.g. a scene layout) is reproducible.
+pub fn seed_rng(seed: u64) {
+  RNG.with(|rng| *rng.borrow_mut() = SmallRng::seed_from_u64(seed));
+}
+
 pub fn random_float() -> f64 {
-  let mut rng = rand::rng();
-  rng.random::<f64>()
+  RNG.with(|rng| rng.borrow_mut().random::<f64>())
 }
 
 pub fn random_float_range(min: f64, max: f64) -> f64 {
@@ -16,6 +28,20 @@ pub fn random_float_range(min: f64, max: f64) -> f64 {
 }
 
 pub fn _random_float_range_direct(min: f64, max: f64) -> f64 {
-  let mut rng = rand::rng();
-  rng.random_range(min..max)
-}
\ No newline at end of file
+  RNG.with(|rng| rng.borrow_mut().random_range(min..max))
+}
+
+/// SplitMix64 finalizer: a cheap, well-mixed 64-bit hash.
+pub fn hash_u64(mut x: u64) -> u64 {
+  x = x.wrapping_add(0x9e3779b97f4a7c15);
+  x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+  x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+  x ^ (x >> 31)
+}
+
+/// Seed for one camera sample: each (seed, pixel, sample index) triple gets
+/// its own random stream, so results do not depend on which thread takes a
+/// sample or when.
+pub fn sample_seed(seed: u64, pixel: u64, sample: u64) -> u64 {
+  hash_u64(hash_u64(seed ^ hash_u64(pixel)) ^ sample)
+}
@@ -0,0 +1,392 @@
+use crate::blue_noise;
+use crate::rtweekend::{hash_u64, random_float};
+
+/// Which sample generator a render draws its random numbers from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SamplerKind {
+  /// Uniform pseudo-random numbers, each drawn on its own.
+  #[default]
+  Independent,
+  /// Jittered strata, shuffled per pixel and dimension.
+  Stratified,
+  /// Halton sequence with a random shift per pixel and dimension.
+  Halton,
+  /// Owen-scrambled Sobol points, padded with a fresh scramble per dimension.
+  Sobol,
+  /// Low-discrepancy points shifted by a blue-noise mask, so the error left
+  /// in neighbouring pixels is uncorrelated and looks like fine grain.
+  BlueNoise,
+}
+
+impl SamplerKind {
+  pub const ALL: [SamplerKind; 5] = [
+    SamplerKind::Independent,
+    SamplerKind::Stratified,
+    SamplerKind::Halton,
+    SamplerKind::Sobol,
+    SamplerKind::BlueNoise,
+  ];
+
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name.to_ascii_lowercase().as_str() {
+      "independent" | "random" => Some(SamplerKind::Independent),
+      "stratified" | "jittered" => Some(SamplerKind::Stratified),
+      "halton" => Some(SamplerKind::Halton),
+      "sobol" => Some(SamplerKind::Sobol),
+      "blue-noise" | "bluenoise" => Some(SamplerKind::BlueNoise),
+      _ => None,
+    }
+  }
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      SamplerKind::Independent => "independent",
+      SamplerKind::Stratified => "stratified",
+      SamplerKind::Halton => "halton",
+      SamplerKind::Sobol => "sobol",
+      SamplerKind::BlueNoise => "blue-noise",
+    }
+  }
+}
+
+/// Source of the sample values used while tracing one camera sample. Each
+/// call moves on to the next dimension, so the n-th value drawn for a sample
+/// always comes from the same dimension of the sequence.
+pub trait Sampler {
+  /// Starts sample `index` of pixel (x, y), from the first dimension.
+  fn start_sample(&mut self, x: usize, y: usize, index: u32);
+
+  /// A value in `[0, 1)`.
+  fn get_1d(&mut self) -> f64;
+
+  /// A point in `[0, 1)²`.
+  fn get_2d(&mut self) -> (f64, f64);
+}
+
+/// Builds a sampler for a render of `samples_per_pixel` samples whose film
+/// was seeded with `seed`.
+pub fn create(kind: SamplerKind, samples_per_pixel: i32, seed: u64) -> Box<dyn Sampler> {
+  match kind {
+    SamplerKind::Independent => Box::new(IndependentSampler),
+    // Time-budgeted renders have no real sample count; cap the strata.
+    SamplerKind::Stratified => Box::new(StratifiedSampler::new(samples_per_pixel.clamp(1, 1 << 16) as u32, seed)),
+    SamplerKind::Halton => Box::new(HaltonSampler::new(seed)),
+    SamplerKind::Sobol => Box::new(SobolSampler::new(seed)),
+    SamplerKind::BlueNoise => Box::new(BlueNoiseSampler::new(seed)),
+  }
+}
+
+/// Draws from the per-sample generator in `rtweekend`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IndependentSampler;
+
+impl Sampler for IndependentSampler {
+  fn start_sample(&mut self, _x: usize, _y: usize, _index: u32) {}
+
+  fn get_1d(&mut self) -> f64 {
+    random_float()
+  }
+
+  fn get_2d(&mut self) -> (f64, f64) {
+    (random_float(), random_float())
+  }
+}
+
+/// Where the current sample is, shared by the structured samplers.
+#[derive(Debug, Clone, Copy, Default)]
+struct SampleState {
+  seed: u64,
+  /// Hash of the seed and the pixel.
+  pixel: u64,
+  x: usize,
+  y: usize,
+  index: u32,
+  dimension: u32,
+}
+
+impl SampleState {
+  fn new(seed: u64) -> Self {
+    SampleState { seed, ..Default::default() }
+  }
+
+  fn start(&mut self, x: usize, y: usize, index: u32) {
+    self.pixel = hash_u64(self.seed ^ hash_u64(((y as u64) << 32) | x as u64));
+    self.x = x;
+    self.y = y;
+    self.index = index;
+    self.dimension = 0;
+  }
+
+  /// Hash identifying the current pixel and dimension, then moves on to the
+  /// next dimension.
+  fn next_dimension(&mut self) -> u64 {
+    let hash = hash_u64(self.pixel ^ ((self.dimension as u64) << 40));
+    self.dimension += 1;
+    hash
+  }
+}
+
+/// Splits each dimension into `strata` cells and visits them in a shuffled
+/// order, jittering within each. Samples beyond `strata` start a new
+/// shuffle.
+#[derive(Debug, Clone, Copy)]
+pub struct StratifiedSampler {
+  strata: u32,
+  state: SampleState,
+}
+
+impl StratifiedSampler {
+  pub fn new(strata: u32, seed: u64) -> Self {
+    StratifiedSampler { strata: strata.max(1), state: SampleState::new(seed) }
+  }
+
+  /// Cell this sample falls in, out of `cells`.
+  fn cell(&mut self, cells: u32) -> u32 {
+    let hash = self.state.next_dimension();
+    let round = self.state.index / cells;
+    permute(self.state.index % cells, cells, hash_u64(hash ^ round as u64) as u32)
+  }
+}
+
+impl Sampler for StratifiedSampler {
+  fn start_sample(&mut self, x: usize, y: usize, index: u32) {
+    self.state.start(x, y, index);
+  }
+
+  fn get_1d(&mut self) -> f64 {
+    let cell = self.cell(self.strata);
+    (cell as f64 + random_float()) / self.strata as f64
+  }
+
+  fn get_2d(&mut self) -> (f64, f64) {
+    // The smallest grid with at least `strata` cells, as square as possible.
+    let columns = (self.strata as f64).sqrt().ceil() as u32;
+    let rows = self.strata.div_ceil(columns);
+    let cell = self.cell(columns * rows);
+    (
+      ((cell % columns) as f64 + random_float()) / columns as f64,
+      ((cell / columns) as f64 + random_float()) / rows as f64,
+    )
+  }
+}
+
+const PRIMES: [u32; 32] = [
+  2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97, 101, 103, 107, 109,
+  113, 127, 131,
+];
+
+/// One prime base per dimension, with the digits of every dimension
+/// scrambled by random permutations chosen per pixel. Without scrambling the
+/// large bases of later dimensions would leave a pixel's first samples
+/// bunched together. Dimensions past the prime table fall back to
+/// independent samples.
+#[derive(Debug, Clone, Copy)]
+pub struct HaltonSampler {
+  state: SampleState,
+}
+
+impl HaltonSampler {
+  pub fn new(seed: u64) -> Self {
+    HaltonSampler { state: SampleState::new(seed) }
+  }
+}
+
+impl Sampler for HaltonSampler {
+  fn start_sample(&mut self, x: usize, y: usize, index: u32) {
+    self.state.start(x, y, index);
+  }
+
+  fn get_1d(&mut self) -> f64 {
+    let dimension = self.state.dimension as usize;
+    let hash = self.state.next_dimension();
+    match PRIMES.get(dimension) {
+      Some(&base) => scrambled_radical_inverse(base, self.state.index, hash),
+      None => random_float(),
+    }
+  }
+
+  fn get_2d(&mut self) -> (f64, f64) {
+    (self.get_1d(), self.get_1d())
+  }
+}
+
+/// The first two Sobol dimensions, with Burley's hash-based Owen scrambling.
+/// Every request gets its own scramble and index shuffle, which pads the
+/// sequence out to any number of dimensions.
+#[derive(Debug, Clone, Copy)]
+pub struct SobolSampler {
+  state: SampleState,
+}
+
+impl SobolSampler {
+  pub fn new(seed: u64) -> Self {
+    SobolSampler { state: SampleState::new(seed) }
+  }
+}
+
+impl Sampler for SobolSampler {
+  fn start_sample(&mut self, x: usize, y: usize, index: u32) {
+    self.state.start(x, y, index);
+  }
+
+  fn get_1d(&mut self) -> f64 {
+    let hash = self.state.next_dimension();
+    to_unit(owen_sobol(self.state.index, 0, hash))
+  }
+
+  fn get_2d(&mut self) -> (f64, f64) {
+    let hash = self.state.next_dimension();
+    (to_unit(owen_sobol(self.state.index, 0, hash)), to_unit(owen_sobol(self.state.index, 1, hash)))
+  }
+}
+
+/// Every pixel uses the same Owen-scrambled Sobol points, shifted toroidally
+/// by the blue-noise mask. Neighbouring pixels get very different shifts, so
+/// what error remains is pushed into high frequencies.
+#[derive(Debug, Clone, Copy)]
+pub struct BlueNoiseSampler {
+  state: SampleState,
+}
+
+impl BlueNoiseSampler {
+  pub fn new(seed: u64) -> Self {
+    BlueNoiseSampler { state: SampleState::new(seed) }
+  }
+
+  /// Like `SampleState::next_dimension`, but the same for every pixel.
+  fn dimension_hash(&mut self) -> u64 {
+    let hash = hash_u64(self.state.seed ^ ((self.state.dimension as u64) << 40));
+    self.state.dimension += 1;
+    hash
+  }
+
+  /// Mask value for the current pixel, read at an offset of its own for
+  /// each dimension so the dimensions are not shifted alike.
+  fn shift(&self, hash: u64) -> f64 {
+    let size = blue_noise::SIZE as u64;
+    blue_noise::sample(self.state.x + (hash % size) as usize, self.state.y + ((hash >> 32) % size) as usize)
+  }
+
+  fn value(&self, axis: u32, hash: u64) -> f64 {
+    (to_unit(owen_sobol(self.state.index, axis, hash)) + self.shift(hash_u64(hash ^ axis as u64))).fract()
+  }
+}
+
+impl Sampler for BlueNoiseSampler {
+  fn start_sample(&mut self, x: usize, y: usize, index: u32) {
+    self.state.start(x, y, index);
+  }
+
+  fn get_1d(&mut self) -> f64 {
+    let hash = self.dimension_hash();
+    self.value(0, hash)
+  }
+
+  fn get_2d(&mut self) -> (f64, f64) {
+    let hash = self.dimension_hash();
+    (self.value(0, hash), self.value(1, hash))
+  }
+}
+
+fn to_unit(bits: u32) -> f64 {
+  bits as f64 / 4294967296.0
+}
+
+/// Digits of `index` in `base`, each put through a permutation picked by
+/// `hash` and its position, and mirrored about the radix point. Leading
+/// zeros are permuted too, up to the precision of an f64.
+fn scrambled_radical_inverse(base: u32, mut index: u32, hash: u64) -> f64 {
+  let inv_base = 1.0 / base as f64;
+  let mut factor = inv_base;
+  let mut result = 0.0;
+  let mut position = 0;
+  while factor > 1e-15 {
+    let digit = permute(index % base, base, hash_u64(hash ^ position) as u32);
+    result += digit as f64 * factor;
+    index /= base;
+    factor *= inv_base;
+    position += 1;
+  }
+  result.min(1.0 - f64::EPSILON / 2.0)
+}
+
+/// Sample `index` of Sobol dimension `axis` (0 or 1), with the index
+/// shuffled and the result scrambled according to `hash`.
+fn owen_sobol(index: u32, axis: u32, hash: u64) -> u32 {
+  let index = nested_uniform_scramble(index, hash as u32);
+  let bits = match axis {
+    0 => index.reverse_bits(),
+    _ => sobol_second_dimension(index),
+  };
+  nested_uniform_scramble(bits, hash_u64(hash ^ (axis as u64 + 1)) as u32)
+}
+
+/// Second Sobol dimension, whose direction numbers follow from the
+/// primitive polynomial x + 1.
+fn sobol_second_dimension(mut index: u32) -> u32 {
+  let mut direction = 1u32 << 31;
+  let mut bits = 0;
+  while index != 0 {
+    if index & 1 != 0 {
+      bits ^= direction;
+    }
+    index >>= 1;
+    direction ^= direction >> 1;
+  }
+  bits
+}
+
+/// Owen scrambling of a 32-bit fraction (Burley 2020, "Practical Hash-based
+/// Owen Scrambling").
+fn nested_uniform_scramble(x: u32, seed: u32) -> u32 {
+  laine_karras_permutation(x.reverse_bits(), seed).reverse_bits()
+}
+
+fn laine_karras_permutation(mut x: u32, seed: u32) -> u32 {
+  x ^= x.wrapping_mul(0x3d20adea);
+  x = x.wrapping_add(seed);
+  x = x.wrapping_mul((seed >> 16) | 1);
+  x ^= x.wrapping_mul(0x05526c56);
+  x ^= x.wrapping_mul(0x53a22864);
+  x
+}
+
+/// Element `i` of a pseudo-random permutation of `0..len` picked by `seed`
+/// (Kensler, "Correlated Multi-Jittered Sampling").
+fn permute(mut i: u32, len: u32, seed: u32) -> u32 {
+  if len <= 1 {
+    return 0;
+  }
+  let mut mask = len - 1;
+  mask |= mask >> 1;
+  mask |= mask >> 2;
+  mask |= mask >> 4;
+  mask |= mask >> 8;
+  mask |= mask >> 16;
+  // Permute within the next power of two and retry until the result lands
+  // inside the range.
+  loop {
+    i ^= seed;
+    i = i.wrapping_mul(0xe170893d);
+    i ^= seed >> 16;
+    i ^= (i & mask) >> 4;
+    i ^= seed >> 8;
+    i = i.wrapping_mul(0x0929eb3f);
+    i ^= seed >> 23;
+    i ^= (i & mask) >> 1;
+    i = i.wrapping_mul(1 | (seed >> 27));
+    i = i.wrapping_mul(0x6935fa69);
+    i ^= (i & mask) >> 11;
+    i = i.wrapping_mul(0x74dcb303);
+    i ^= (i & mask) >> 2;
+    i = i.wrapping_mul(0x9e501cc3);
+    i ^= (i & mask) >> 2;
+    i = i.wrapping_mul(0xc860a3df);
+    i &= mask;
+    i ^= i >> 5;
+    if i < len {
+      break;
+    }
+  }
+  i.wrapping_add(seed) % len
+}
@@ -17,7 +17,7 @@ impl Sphere {
     Sphere { 
       center, 
       radius: radius.max(0.0),
-      material : material,
+      material,
     }
   }
 
@@ -32,32 +32,79 @@ impl Sphere {
 
 impl Hittable for Sphere {
   fn hit(&self, ray: &Ray, ray_t: Interval, rec: &mut HitRecord) -> bool {
-      let oc = self.center - *ray.origin();
-      let a = ray.direction().length_squared();
-      let h = dot(ray.direction(), &oc);
-      let c = oc.length_squared() - self.radius * self.radius;
-
-      let discriminant = h * h - a * c;
-      if discriminant < 0.0 {
+      if !hit_sphere(self.center, self.radius, ray, ray_t, rec) {
         return false;
       }
+      rec.mat = Some(self.material.clone());
+      rec.object_key = self as *const Sphere as usize;
+      true
+  }
 
-      let sqrt_discriminant = discriminant.sqrt();
+  fn bounding_box(&self) -> crate::bvh::AABB {
+    self.bounding_box()
+  }
+}
 
-      let mut root = (h - sqrt_discriminant) / a;
-      if !ray_t.surrounds(root) {
-        root = (h + sqrt_discriminant) / a;
-        if !ray_t.surrounds(root) {
-          return false;
-        }
-      }
+/// A sphere whose centre moves through keyframes, linearly in between and
+/// holding still before the first and after the last. Rays see it where it
+/// is at their own time, which blurs it across the shutter interval.
+#[derive(Clone)]
+pub struct MovingSphere {
+  /// `(time, center)` pairs in time order.
+  keyframes: Vec<(f64, Point3)>,
+  radius: f64,
+  material: Arc<dyn Material>
+}
 
-      rec.t = root;
-      rec.p = ray.at(rec.t);
-      let outward_normal = (rec.p - self.center) /self.radius;
-      rec.set_face_normal(ray, &outward_normal);
-      rec.mat = Some(self.material.clone());
+impl MovingSphere {
+  /// Panics if `keyframes` is empty.
+  pub fn new(mut keyframes: Vec<(f64, Point3)>, radius: f64, material: Arc<dyn Material>) -> Self {
+    assert!(!keyframes.is_empty(), "a moving sphere needs at least one keyframe");
+    keyframes.sort_by(|a, b| a.0.total_cmp(&b.0));
+    MovingSphere {
+      keyframes,
+      radius: radius.max(0.0),
+      material,
+    }
+  }
+
+  /// Moves from `center0` at time 0 to `center1` at time 1.
+  pub fn linear(center0: Point3, center1: Point3, radius: f64, material: Arc<dyn Material>) -> Self {
+    Self::new(vec![(0.0, center0), (1.0, center1)], radius, material)
+  }
 
+  pub fn center(&self, time: f64) -> Point3 {
+    let next = self.keyframes.partition_point(|&(t, _)| t <= time);
+    if next == 0 {
+      return self.keyframes[0].1;
+    }
+    if next == self.keyframes.len() {
+      return self.keyframes[next - 1].1;
+    }
+    let (t0, c0) = self.keyframes[next - 1];
+    let (t1, c1) = self.keyframes[next];
+    c0 + ((time - t0) / (t1 - t0)) * (c1 - c0)
+  }
+
+  /// Covers the sphere at every keyframe; in between the centre stays
+  /// within their convex hull, so that covers the whole path.
+  pub fn bounding_box(&self) -> crate::bvh::AABB {
+    let rvec = Vec3::from_values(self.radius, self.radius, self.radius);
+    self.keyframes
+      .iter()
+      .map(|&(_, center)| crate::bvh::AABB::from_points(center - rvec, center + rvec))
+      .reduce(|a, b| crate::bvh::AABB::from_boxes(&a, &b))
+      .unwrap()
+  }
+}
+
+impl Hittable for MovingSphere {
+  fn hit(&self, ray: &Ray, ray_t: Interval, rec: &mut HitRecord) -> bool {
+      if !hit_sphere(self.center(ray.time()), self.radius, ray, ray_t, rec) {
+        return false;
+      }
+      rec.mat = Some(self.material.clone());
+      rec.object_key = self as *const MovingSphere as usize;
       true
   }
 
@@ -66,6 +113,48 @@ impl Hittable for Sphere {
   }
 }
 
+impl std::fmt::Debug for MovingSphere {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+      f.debug_struct("MovingSphere")
+        .field("keyframes", &self.keyframes)
+        .field("radius", &self.radius)
+        .field("material", &"<Material>")
+        .finish()
+  }
+}
+
+/// Fills in the geometry of the nearest hit within `ray_t` of a sphere
+/// at `center`, leaving the material and identity to the caller.
+fn hit_sphere(center: Point3, radius: f64, ray: &Ray, ray_t: Interval, rec: &mut HitRecord) -> bool {
+  let oc = center - *ray.origin();
+  let a = ray.direction().length_squared();
+  let h = dot(ray.direction(), &oc);
+  let c = oc.length_squared() - radius * radius;
+
+  let discriminant = h * h - a * c;
+  if discriminant < 0.0 {
+    return false;
+  }
+
+  let sqrt_discriminant = discriminant.sqrt();
+
+  let mut root = (h - sqrt_discriminant) / a;
+  if !ray_t.surrounds(root) {
+    root = (h + sqrt_discriminant) / a;
+    if !ray_t.surrounds(root) {
+      return false;
+    }
+  }
+
+  rec.t = root;
+  rec.p = ray.at(rec.t);
+  let outward_normal = (rec.p - center) / radius;
+  rec.set_face_normal(ray, &outward_normal);
+  rec.time = ray.time();
+
+  true
+}
+
 impl std::fmt::Debug for Sphere {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
       f.debug_struct("Sphere")
@@ -0,0 +1,64 @@
+/// Settings for rendering a left and right eye pair into one image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stereo {
+  /// Distance between the eyes in world units; 0 uses a thirtieth of the
+  /// focus distance, the usual rule of thumb for comfortable depth.
+  pub interocular: f64,
+  pub convergence: Convergence,
+  pub layout: StereoLayout,
+}
+
+impl Stereo {
+  pub fn new(layout: StereoLayout) -> Self {
+    Stereo { interocular: 0.0, convergence: Convergence::default(), layout }
+  }
+
+  /// Eye separation for a camera focused `focus_dist` away.
+  pub fn interocular_distance(&self, focus_dist: f64) -> f64 {
+    if self.interocular > 0.0 { self.interocular } else { focus_dist / 30.0 }
+  }
+}
+
+/// How the two eyes are aimed so that objects at the focus distance line
+/// up in both images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Convergence {
+  /// Parallel eyes sharing one focus plane, i.e. asymmetric frusta. Keeps
+  /// vertical parallax out of the corners. Gives no parallax at all with
+  /// an orthographic projection, whose rays are parallel anyway.
+  #[default]
+  OffAxis,
+  /// Each eye turned inwards to look at the convergence point.
+  ToeIn,
+}
+
+impl Convergence {
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name.to_ascii_lowercase().as_str() {
+      "off-axis" | "parallel" => Some(Convergence::OffAxis),
+      "toe-in" => Some(Convergence::ToeIn),
+      _ => None,
+    }
+  }
+}
+
+/// Where the two eyes go in the output image; the left eye always comes
+/// first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StereoLayout {
+  /// Left eye on the left, right eye on the right.
+  #[default]
+  SideBySide,
+  /// Left eye on top, right eye below, the usual layout for 360° stereo.
+  TopBottom,
+}
+
+impl StereoLayout {
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name.to_ascii_lowercase().as_str() {
+      "side-by-side" | "sbs" => Some(StereoLayout::SideBySide),
+      "top-bottom" | "over-under" | "tb" => Some(StereoLayout::TopBottom),
+      _ => None,
+    }
+  }
+}
@@ -0,0 +1,179 @@
+use std::f64::consts::PI;
+
+/// Order in which the scheduler hands tiles to worker threads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TileOrder {
+  /// Left to right, top to bottom.
+  Scanline,
+  /// Outwards from the centre of the image, ring by ring.
+  #[default]
+  Spiral,
+  /// Along a Hilbert curve, keeping consecutive tiles adjacent.
+  Hilbert,
+  /// Z-order (Morton) curve.
+  Morton,
+}
+
+impl TileOrder {
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name.to_ascii_lowercase().as_str() {
+      "scanline" | "rows" => Some(TileOrder::Scanline),
+      "spiral" | "center" => Some(TileOrder::Spiral),
+      "hilbert" => Some(TileOrder::Hilbert),
+      "morton" | "z-order" => Some(TileOrder::Morton),
+      _ => None,
+    }
+  }
+}
+
+/// A rectangle of pixels, `x0..x1` by `y0..y1`, rendered as one work unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+  /// Position of the tile in scheduling order.
+  pub index: usize,
+  pub x0: usize,
+  pub y0: usize,
+  pub x1: usize,
+  pub y1: usize,
+}
+
+impl Tile {
+  pub fn width(&self) -> usize {
+    self.x1 - self.x0
+  }
+
+  pub fn height(&self) -> usize {
+    self.y1 - self.y0
+  }
+
+  pub fn pixel_count(&self) -> usize {
+    self.width() * self.height()
+  }
+
+  /// Image coordinates of every pixel in the tile, row by row.
+  pub fn pixels(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+    (self.y0..self.y1).flat_map(move |y| (self.x0..self.x1).map(move |x| (x, y)))
+  }
+}
+
+/// Splits a `width` x `height` image into tiles of at most `tile_size`
+/// pixels square and sorts them into `order`.
+pub fn generate(width: usize, height: usize, tile_size: usize, order: TileOrder) -> Vec<Tile> {
+  generate_in(0, 0, width, height, tile_size, order)
+}
+
+/// Like `generate`, but only covers the region `x0..x1` by `y0..y1`.
+pub fn generate_in(x0: usize, y0: usize, x1: usize, y1: usize, tile_size: usize, order: TileOrder) -> Vec<Tile> {
+  let tile_size = tile_size.max(1);
+  let columns = (x1 - x0).div_ceil(tile_size);
+  let rows = (y1 - y0).div_ceil(tile_size);
+
+  let mut grid: Vec<(usize, usize)> = (0..rows).flat_map(|ty| (0..columns).map(move |tx| (tx, ty))).collect();
+  match order {
+    TileOrder::Scanline => {}
+    TileOrder::Spiral => {
+      // Ring number first, then the angle around the centre, which walks each
+      // ring as one turn of the spiral.
+      let cx = (columns as f64 - 1.0) / 2.0;
+      let cy = (rows as f64 - 1.0) / 2.0;
+      let key = |&(tx, ty): &(usize, usize)| {
+        let dx = tx as f64 - cx;
+        let dy = ty as f64 - cy;
+        let ring = dx.abs().max(dy.abs()).round() as u64;
+        let angle = (dy.atan2(dx) + 2.0 * PI) % (2.0 * PI);
+        (ring, (angle * 1e6) as u64)
+      };
+      grid.sort_by_key(key);
+    }
+    TileOrder::Hilbert => {
+      let side = columns.max(rows).next_power_of_two();
+      grid.sort_by_key(|&(tx, ty)| hilbert_index(side, tx, ty));
+    }
+    TileOrder::Morton => grid.sort_by_key(|&(tx, ty)| morton_index(tx, ty)),
+  }
+
+  grid
+    .into_iter()
+    .enumerate()
+    .map(|(index, (tx, ty))| Tile {
+      index,
+      x0: x0 + tx * tile_size,
+      y0: y0 + ty * tile_size,
+      x1: (x0 + (tx + 1) * tile_size).min(x1),
+      y1: (y0 + (ty + 1) * tile_size).min(y1),
+    })
+    .collect()
+}
+
+/// Distance of (x, y) along the Hilbert curve filling a `side` x `side`
+/// square, `side` being a power of two.
+fn hilbert_index(side: usize, mut x: usize, mut y: usize) -> usize {
+  let mut d = 0;
+  let mut s = side / 2;
+  while s > 0 {
+    let rx = usize::from(x & s > 0);
+    let ry = usize::from(y & s > 0);
+    d += s * s * ((3 * rx) ^ ry);
+    // Rotate the quadrant so the curve stays continuous.
+    if ry == 0 {
+      if rx == 1 {
+        x = side - 1 - x;
+        y = side - 1 - y;
+      }
+      std::mem::swap(&mut x, &mut y);
+    }
+    s /= 2;
+  }
+  d
+}
+
+/// Interleaves the bits of x and y.
+fn morton_index(x: usize, y: usize) -> u64 {
+  fn spread(mut v: u64) -> u64 {
+    v &= 0xffff_ffff;
+    v = (v | (v << 16)) & 0x0000_ffff_0000_ffff;
+    v = (v | (v << 8)) & 0x00ff_00ff_00ff_00ff;
+    v = (v | (v << 4)) & 0x0f0f_0f0f_0f0f_0f0f;
+    v = (v | (v << 2)) & 0x3333_3333_3333_3333;
+    (v | (v << 1)) & 0x5555_5555_5555_5555
+  }
+  spread(x as u64) | (spread(y as u64) << 1)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const ORDERS: [TileOrder; 4] = [TileOrder::Scanline, TileOrder::Spiral, TileOrder::Hilbert, TileOrder::Morton];
+
+  /// Counts how often each pixel of the region is handed out.
+  fn coverage(tiles: &[Tile], (x0, y0, x1, y1): (usize, usize, usize, usize), tile_size: usize) -> Vec<u32> {
+    let width = x1 - x0;
+    let mut counts = vec![0; width * (y1 - y0)];
+    for (index, tile) in tiles.iter().enumerate() {
+      assert_eq!(tile.index, index);
+      assert!(tile.x0 >= x0 && tile.x1 <= x1 && tile.y0 >= y0 && tile.y1 <= y1, "{:?} outside the region", tile);
+      assert!(tile.width() <= tile_size && tile.height() <= tile_size, "{:?} too large", tile);
+      for (x, y) in tile.pixels() {
+        counts[(y - y0) * width + (x - x0)] += 1;
+      }
+    }
+    counts
+  }
+
+  #[test]
+  fn every_order_covers_each_pixel_once() {
+    for order in ORDERS {
+      for (width, height, tile_size) in [(37, 23, 8), (23, 37, 8), (100, 3, 32), (1, 1, 8), (64, 64, 16)] {
+        let tiles = generate(width, height, tile_size, order);
+        let counts = coverage(&tiles, (0, 0, width, height), tile_size);
+        assert!(counts.iter().all(|&count| count == 1), "{:?} on {}x{} with {} px tiles", order, width, height, tile_size);
+      }
+
+      // A crop region that starts off the tile grid.
+      let region = (5, 3, 42, 26);
+      let tiles = generate_in(region.0, region.1, region.2, region.3, 8, order);
+      assert!(coverage(&tiles, region, 8).iter().all(|&count| count == 1), "{:?} on a crop region", order);
+    }
+  }
+}
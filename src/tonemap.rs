@@ -0,0 +1,176 @@
+use crate::color::{luminance, quantize, Color, Dither, TransferFunction};
+
+/// Curve used to compress scene-referred radiance into the displayable
+/// `[0, 1]` range. Every curve returns display-linear values; the output
+/// encoding (gamma) is applied afterwards by the writer.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ToneMap {
+  /// Hard clip at 1.0, the tracer's original behaviour.
+  #[default]
+  Clamp,
+  /// `L / (1 + L)` on luminance, preserving hue.
+  Reinhard,
+  /// Reinhard with a white point: luminance `white` and above maps to 1.0.
+  ReinhardExtended { white: f64 },
+  /// John Hable's filmic curve from Uncharted 2.
+  Hable,
+  /// Stephen Hill's fit of the ACES RRT + sRGB ODT.
+  Aces,
+  /// Troy Sobotka's AgX base look, using the common polynomial fit of its sigmoid.
+  Agx,
+}
+
+impl ToneMap {
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name.to_ascii_lowercase().as_str() {
+      "clamp" | "none" => Some(ToneMap::Clamp),
+      "reinhard" => Some(ToneMap::Reinhard),
+      "reinhard-extended" => Some(ToneMap::ReinhardExtended { white: 4.0 }),
+      "hable" | "uncharted2" | "filmic" => Some(ToneMap::Hable),
+      "aces" => Some(ToneMap::Aces),
+      "agx" => Some(ToneMap::Agx),
+      _ => None,
+    }
+  }
+
+  pub fn apply(&self, color: Color) -> Color {
+    match *self {
+      ToneMap::Clamp => clamp01(color),
+      ToneMap::Reinhard => scale_luminance(color, |l| l / (1.0 + l)),
+      ToneMap::ReinhardExtended { white } => {
+        let white_sq = (white * white).max(1e-12);
+        scale_luminance(color, |l| l * (1.0 + l / white_sq) / (1.0 + l))
+      }
+      ToneMap::Hable => hable(color),
+      ToneMap::Aces => aces(color),
+      ToneMap::Agx => agx(color),
+    }
+  }
+}
+
+/// Scene-to-display transform applied to every LDR output: exposure, the
+/// tone curve, the transfer function, then dithered quantization.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DisplayTransform {
+  /// Exposure adjustment in stops; radiance is scaled by `2^exposure`.
+  pub exposure: f64,
+  pub tone_map: ToneMap,
+  pub transfer: TransferFunction,
+  pub dither: Dither,
+}
+
+impl DisplayTransform {
+  /// Maps radiance to display-linear values in `[0, 1]`.
+  pub fn apply(&self, color: Color) -> Color {
+    let exposed = if self.exposure == 0.0 { color } else { 2f64.powf(self.exposure) * color };
+    self.tone_map.apply(exposed)
+  }
+
+  /// Full pipeline for pixel (x, y): returns integer codes in `0..=max_value`.
+  pub fn encode(&self, color: Color, max_value: u32, x: usize, y: usize) -> [u32; 3] {
+    let display = self.apply(color);
+    let mut codes = [0; 3];
+    for (channel, code) in codes.iter_mut().enumerate() {
+      let encoded = self.transfer.encode(display[channel]);
+      *code = quantize(encoded, max_value, self.dither.offset(x, y, channel));
+    }
+    codes
+  }
+}
+
+fn clamp01(color: Color) -> Color {
+  Color::from_values(color.x().clamp(0.0, 1.0), color.y().clamp(0.0, 1.0), color.z().clamp(0.0, 1.0))
+}
+
+fn scale_luminance(color: Color, curve: impl Fn(f64) -> f64) -> Color {
+  let l = luminance(&color);
+  if l <= 0.0 {
+    return Color::new();
+  }
+  clamp01(color * (curve(l) / l))
+}
+
+fn hable(color: Color) -> Color {
+  const EXPOSURE_BIAS: f64 = 2.0;
+  const WHITE: f64 = 11.2;
+
+  fn partial(x: f64) -> f64 {
+    const A: f64 = 0.15;
+    const B: f64 = 0.50;
+    const C: f64 = 0.10;
+    const D: f64 = 0.20;
+    const E: f64 = 0.02;
+    const F: f64 = 0.30;
+    ((x * (A * x + C * B) + D * E) / (x * (A * x + B) + D * F)) - E / F
+  }
+
+  let white_scale = 1.0 / partial(WHITE);
+  let curve = |x: f64| partial(EXPOSURE_BIAS * x.max(0.0)) * white_scale;
+  clamp01(Color::from_values(curve(color.x()), curve(color.y()), curve(color.z())))
+}
+
+fn mat3_mul(m: &[[f64; 3]; 3], c: Color) -> Color {
+  Color::from_values(
+    m[0][0] * c.x() + m[0][1] * c.y() + m[0][2] * c.z(),
+    m[1][0] * c.x() + m[1][1] * c.y() + m[1][2] * c.z(),
+    m[2][0] * c.x() + m[2][1] * c.y() + m[2][2] * c.z(),
+  )
+}
+
+fn aces(color: Color) -> Color {
+  // sRGB => XYZ => D65_2_D60 => AP1 => RRT_SAT
+  const INPUT: [[f64; 3]; 3] = [
+    [0.59719, 0.35458, 0.04823],
+    [0.07600, 0.90834, 0.01566],
+    [0.02840, 0.13383, 0.83777],
+  ];
+  // ODT_SAT => XYZ => D60_2_D65 => sRGB
+  const OUTPUT: [[f64; 3]; 3] = [
+    [1.60475, -0.53108, -0.07367],
+    [-0.10208, 1.10813, -0.00605],
+    [-0.00327, -0.07276, 1.07602],
+  ];
+
+  let rrt_and_odt_fit = |v: f64| {
+    let a = v * (v + 0.0245786) - 0.000090537;
+    let b = v * (0.983729 * v + 0.4329510) + 0.238081;
+    a / b
+  };
+
+  let c = mat3_mul(&INPUT, color);
+  let c = Color::from_values(rrt_and_odt_fit(c.x()), rrt_and_odt_fit(c.y()), rrt_and_odt_fit(c.z()));
+  clamp01(mat3_mul(&OUTPUT, c))
+}
+
+fn agx(color: Color) -> Color {
+  const INSET: [[f64; 3]; 3] = [
+    [0.842479062253094, 0.0784335999999992, 0.0792237451477643],
+    [0.0423282422610123, 0.878468636469772, 0.0791661274605434],
+    [0.0423756549057051, 0.0784336, 0.879142973793104],
+  ];
+  const OUTSET: [[f64; 3]; 3] = [
+    [1.19687900512017, -0.0980208811401368, -0.0990297440797205],
+    [-0.0528968517574562, 1.15190312990417, -0.0989611768448433],
+    [-0.0529716355144438, -0.0980434501171241, 1.15107367264116],
+  ];
+  const MIN_EV: f64 = -12.47393;
+  const MAX_EV: f64 = 4.026069;
+
+  // Polynomial approximation of the AgX sigmoid over the normalized log range.
+  let contrast = |x: f64| {
+    let x2 = x * x;
+    let x4 = x2 * x2;
+    15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x - 0.00232
+  };
+  let encode = |v: f64| {
+    let log = v.max(1e-10).log2().clamp(MIN_EV, MAX_EV);
+    contrast((log - MIN_EV) / (MAX_EV - MIN_EV))
+  };
+
+  let c = mat3_mul(&INSET, color);
+  let c = mat3_mul(&OUTSET, Color::from_values(encode(c.x()), encode(c.y()), encode(c.z())));
+  // The sigmoid produces display-encoded values; undo the 2.2 power so the
+  // writer's encoding is applied exactly once.
+  let c = clamp01(c);
+  Color::from_values(c.x().powf(2.2), c.y().powf(2.2), c.z().powf(2.2))
+}
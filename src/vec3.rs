@@ -221,6 +221,27 @@ pub fn random_in_unit_disk() -> Vec3 {
   }
 }
 
+/// Maps a point of the unit square to a unit vector, uniformly over the
+/// sphere.
+pub fn sample_unit_vector(u: f64, v: f64) -> Vec3 {
+  let z = 1.0 - 2.0 * u;
+  let r = (1.0 - z * z).max(0.0).sqrt();
+  let phi = 2.0 * std::f64::consts::PI * v;
+  Vec3::from_values(r * phi.cos(), r * phi.sin(), z)
+}
+
+/// Maps a point of the unit square into the unit disk with Shirley and
+/// Chiu's concentric mapping, which keeps strata compact.
+pub fn sample_unit_disk(u: f64, v: f64) -> Vec3 {
+  let (a, b) = (2.0 * u - 1.0, 2.0 * v - 1.0);
+  if a == 0.0 && b == 0.0 {
+    return Vec3::new();
+  }
+  let quarter = std::f64::consts::FRAC_PI_4;
+  let (r, theta) = if a.abs() > b.abs() { (a, quarter * (b / a)) } else { (b, 2.0 * quarter - quarter * (a / b)) };
+  Vec3::from_values(r * theta.cos(), r * theta.sin(), 0.0)
+}
+
 pub fn _random_on_hemisphere(normal: &Vec3) -> Vec3 {
   let on_unit_sphere = random_unit_vector();
   if dot(&on_unit_sphere, normal) > 0.0 {